@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use ethers::abi::Abi;
+use ethers::prelude::*;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::contracts::{fetch_deployed_contract, missing_functions, PufferContracts};
+
+use super::puffer_contracts;
+use crate::contract_abis;
+
+/// A deployed contract compared against its bundled ABI
+#[derive(Clone, Debug, Serialize)]
+pub struct ContractCheck {
+    pub name: String,
+    pub address: Address,
+    pub implementation: Option<Address>,
+    pub upgrade_interface_version: Option<String>,
+    pub missing_functions: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+fn contract_address(contracts: &PufferContracts, name: &str) -> Option<Address> {
+    match name {
+        "PufferProtocol" => Some(contracts.puffer_protocol),
+        "GuardianModule" => Some(contracts.guardian_module),
+        "PufferOracle" => Some(contracts.puffer_oracle),
+        "PufferVault" => Some(contracts.puffer_vault),
+        "ValidatorTicket" => Some(contracts.validator_ticket),
+        // one contract per module, not part of the deployment addresses
+        _ => None,
+    }
+}
+
+fn is_uups(abi: &Abi) -> bool {
+    abi.function("UPGRADE_INTERFACE_VERSION").is_ok()
+}
+
+async fn check_contract<J, E>(
+    provider: Arc<Provider<J>>,
+    name: &str,
+    address: Address,
+    abi: &Abi,
+) -> AppResult<ContractCheck>
+where
+    J: JsonRpcClient<Error = E> + 'static,
+{
+    let deployed = fetch_deployed_contract(provider, address).await?;
+
+    let mut problems = Vec::new();
+    let mut missing = Vec::new();
+    if deployed.code.is_empty() {
+        match deployed.implementation {
+            Some(implementation) => {
+                problems.push(format!("No code at implementation {implementation:?}"))
+            }
+            None => problems.push("No code at address".to_string()),
+        }
+    } else {
+        missing = missing_functions(abi, &deployed.code);
+        if !missing.is_empty() {
+            problems.push(format!(
+                "{} function(s) of the bundled ABI not found in the deployed code",
+                missing.len()
+            ));
+        }
+    }
+    if is_uups(abi) {
+        if deployed.implementation.is_none() {
+            problems.push("No ERC-1967 implementation, the bundled ABI is upgradeable".to_string());
+        }
+        if deployed.upgrade_interface_version.is_none() {
+            problems.push("Failed to read UPGRADE_INTERFACE_VERSION".to_string());
+        }
+    }
+
+    Ok(ContractCheck {
+        name: name.to_string(),
+        address,
+        implementation: deployed.implementation,
+        upgrade_interface_version: deployed.upgrade_interface_version,
+        missing_functions: missing,
+        problems,
+    })
+}
+
+fn print_check(check: &ContractCheck) {
+    let status = if check.problems.is_empty() {
+        "OK"
+    } else {
+        "MISMATCH"
+    };
+    println!("{status} {}: {:?}", check.name, check.address);
+    if let Some(implementation) = check.implementation {
+        println!("  implementation: {implementation:?}");
+    }
+    if let Some(version) = &check.upgrade_interface_version {
+        println!("  UPGRADE_INTERFACE_VERSION: {version}");
+    }
+    for problem in check.problems.iter() {
+        println!("  {problem}");
+    }
+    for function in check.missing_functions.iter() {
+        println!("  missing: {function}");
+    }
+}
+
+pub async fn check_contracts(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let contracts = puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address).await?;
+    let provider = Arc::new(provider);
+
+    let mut checks = Vec::new();
+    for (name, abi) in contract_abis() {
+        let Some(address) = contract_address(&contracts, name) else {
+            continue;
+        };
+        checks.push(check_contract(provider.clone(), name, address, abi).await?);
+    }
+
+    if json {
+        let checks_json = serde_json::to_string_pretty(&checks).map_err(|err| {
+            let error_msg = format!("Failed to serialize contract checks: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{checks_json}");
+    } else {
+        for check in checks.iter() {
+            print_check(check);
+        }
+    }
+
+    if checks.iter().any(|check| !check.problems.is_empty()) {
+        return Ok(1);
+    }
+    Ok(0)
+}
@@ -0,0 +1,123 @@
+pub mod check;
+
+use std::sync::Arc;
+
+use clap::Subcommand;
+use ethers::prelude::*;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::profile::Profile;
+use coral_lib::utils;
+use coral_lib::utils::contracts::{default_cache_path, get_puffer_contracts, PufferContracts};
+
+use crate::commands::resolve;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ContractsCommand {
+    #[command(about = "Check the deployed contracts against the bundled ABIs")]
+    Check {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "json")]
+        json: bool,
+    },
+}
+
+impl ContractsCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Check {
+                rpc_url,
+                puffer_protocol_address,
+                json,
+            } => {
+                check::check_contracts(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    json,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Contract addresses of the PufferProtocol deployment, from the cache when possible
+pub async fn puffer_contracts<J>(
+    provider: &Provider<J>,
+    chain_id: u64,
+    puffer_protocol_address: Address,
+) -> AppResult<PufferContracts>
+where
+    J: JsonRpcClient + Clone + 'static,
+{
+    let cache_path = default_cache_path();
+    let contracts = get_puffer_contracts(
+        Arc::new(provider.clone()),
+        chain_id,
+        puffer_protocol_address,
+        cache_path.as_deref(),
+        false,
+    )
+    .await?;
+    Ok(contracts)
+}
+
+#[derive(Serialize)]
+struct ContractsOutput {
+    chain_id: u64,
+    #[serde(flatten)]
+    contracts: PufferContracts,
+}
+
+pub async fn show_contracts(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    refresh: bool,
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?.as_u64();
+
+    let cache_path = default_cache_path();
+    let contracts = get_puffer_contracts(
+        Arc::new(provider),
+        chain_id,
+        puffer_protocol_address_h160,
+        cache_path.as_deref(),
+        refresh,
+    )
+    .await?;
+
+    if json {
+        let output = ContractsOutput {
+            chain_id,
+            contracts,
+        };
+        let output_json = serde_json::to_string_pretty(&output).map_err(|err| {
+            let error_msg = format!("Failed to serialize contracts: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{output_json}");
+        return Ok(0);
+    }
+
+    println!("Chain ID: {chain_id}");
+    println!("PufferProtocol: {:?}", contracts.puffer_protocol);
+    println!("PufferOracle: {:?}", contracts.puffer_oracle);
+    println!("ValidatorTicket: {:?}", contracts.validator_ticket);
+    println!("GuardianModule: {:?}", contracts.guardian_module);
+    println!("PufferVault: {:?}", contracts.puffer_vault);
+    println!("PufferModuleManager: {:?}", contracts.puffer_module_manager);
+    println!(
+        "Beacon deposit contract: {:?}",
+        contracts.beacon_deposit_contract
+    );
+    Ok(0)
+}
@@ -0,0 +1,36 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils::mock_enclave::{MockEnclave, MockEnclaveConfig, MockEvidence};
+
+fn read_evidence(path: &PathBuf) -> AppResult<serde_json::Value> {
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|err| {
+        let error_msg = format!("Failed to parse evidence file '{}': {err}", path.display());
+        AppError::new(AppErrorKind::ParseError, error_msg)
+    })
+}
+
+pub async fn mock_enclave(
+    listen: SocketAddr,
+    keys: Vec<String>,
+    evidence_file: Option<PathBuf>,
+    password: String,
+    healthy: bool,
+) -> AppResult<i32> {
+    let evidence = match evidence_file {
+        Some(path) => MockEvidence::Canned(read_evidence(&path)?),
+        None => MockEvidence::Local { password },
+    };
+    let config = MockEnclaveConfig {
+        keys,
+        evidence,
+        healthy,
+    };
+
+    let enclave = MockEnclave::start(config, listen).await?;
+    println!("Mock enclave listening on {}", enclave.url());
+    enclave.wait().await;
+    Ok(0)
+}
@@ -0,0 +1,48 @@
+pub mod mock_enclave;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use coral_lib::error::AppResult;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum DevCommand {
+    #[command(about = "Serve a mock Secure-Signer enclave for demos")]
+    MockEnclave {
+        #[arg(long = "listen", default_value = "127.0.0.1:9001")]
+        listen: SocketAddr,
+        #[arg(long = "key", help = "BLS public key to list, can be repeated")]
+        keys: Vec<String>,
+        #[arg(
+            long = "evidence-file",
+            help = "Keygen payload returned for every keygen, instead of generating keys"
+        )]
+        evidence_file: Option<PathBuf>,
+        #[arg(
+            long = "password",
+            default_value = "coral-mock-enclave",
+            help = "Password of locally generated keystores"
+        )]
+        password: String,
+        #[arg(long = "unhealthy", help = "Fail the health check")]
+        unhealthy: bool,
+    },
+}
+
+impl DevCommand {
+    pub async fn execute(self) -> AppResult<i32> {
+        match self {
+            Self::MockEnclave {
+                listen,
+                keys,
+                evidence_file,
+                password,
+                unhealthy,
+            } => {
+                mock_enclave::mock_enclave(listen, keys, evidence_file, password, !unhealthy).await
+            }
+        }
+    }
+}
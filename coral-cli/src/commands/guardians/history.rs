@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use ethers::abi::RawLog;
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+
+use crate::commands::contracts::puffer_contracts;
+use crate::guardian_module::{
+    GuardianAddedFilter, GuardianModuleEvents, GuardianRemovedFilter, MrEnclaveChangedFilter,
+    RotatedGuardianKeyFilter, ThresholdChangedFilter,
+};
+use crate::GuardianModule;
+
+use super::show::GuardianSet;
+use super::{check_registration_files, print_registration_file_checks};
+
+/// A change to the guardian set.
+/// `old_value` and `new_value` are set for threshold and MRENCLAVE changes.
+#[derive(Clone, Debug, Serialize)]
+pub struct GuardianEvent {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub tx_hash: String,
+    pub event: String,
+    pub guardian: Option<Address>,
+    pub guardian_enclave: Option<Address>,
+    pub pub_key: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+fn tracked_event_signatures() -> Vec<H256> {
+    vec![
+        GuardianAddedFilter::signature(),
+        GuardianRemovedFilter::signature(),
+        RotatedGuardianKeyFilter::signature(),
+        ThresholdChangedFilter::signature(),
+        MrEnclaveChangedFilter::signature(),
+    ]
+}
+
+fn decode_guardian_log(log: &Log) -> Option<GuardianEvent> {
+    let raw_log = RawLog::from(log.clone());
+    let mut event = GuardianEvent {
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+        log_index: log.log_index.unwrap_or_default().as_u64(),
+        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+        event: String::new(),
+        guardian: None,
+        guardian_enclave: None,
+        pub_key: None,
+        old_value: None,
+        new_value: None,
+    };
+    match GuardianModuleEvents::decode_log(&raw_log).ok()? {
+        GuardianModuleEvents::GuardianAddedFilter(e) => {
+            event.event = "GuardianAdded".to_string();
+            event.guardian = Some(e.guardian);
+        }
+        GuardianModuleEvents::GuardianRemovedFilter(e) => {
+            event.event = "GuardianRemoved".to_string();
+            event.guardian = Some(e.guardian);
+        }
+        GuardianModuleEvents::RotatedGuardianKeyFilter(e) => {
+            event.event = "RotatedGuardianKey".to_string();
+            event.guardian = Some(e.guardian);
+            event.guardian_enclave = Some(e.guardian_enclave);
+            event.pub_key = Some(format!("0x{}", hex::encode(&e.pub_key)));
+        }
+        GuardianModuleEvents::ThresholdChangedFilter(e) => {
+            event.event = "ThresholdChanged".to_string();
+            event.old_value = Some(e.old_threshold.to_string());
+            event.new_value = Some(e.new_threshold.to_string());
+        }
+        GuardianModuleEvents::MrEnclaveChangedFilter(e) => {
+            event.event = "MrEnclaveChanged".to_string();
+            event.old_value = Some(format!("0x{}", hex::encode(e.old_mr_enclave)));
+            event.new_value = Some(format!("0x{}", hex::encode(e.new_mr_enclave)));
+        }
+        _ => return None,
+    }
+    Some(event)
+}
+
+fn print_event(event: &GuardianEvent) {
+    let mut line = format!("block {}: {}", event.block_number, event.event);
+    if let Some(guardian) = event.guardian {
+        line.push_str(&format!(" guardian={guardian:?}"));
+    }
+    if let Some(guardian_enclave) = event.guardian_enclave {
+        line.push_str(&format!(" enclave={guardian_enclave:?}"));
+    }
+    if let Some(pub_key) = &event.pub_key {
+        line.push_str(&format!(" pub_key={pub_key}"));
+    }
+    if let (Some(old_value), Some(new_value)) = (&event.old_value, &event.new_value) {
+        line.push_str(&format!(" {old_value} -> {new_value}"));
+    }
+    println!("{line}");
+}
+
+pub async fn guardian_history(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    from_block: u64,
+    to_block: Option<u64>,
+    chunk_size: u64,
+    registration_files: &[PathBuf],
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    if chunk_size == 0 {
+        let error_msg = "Chunk size must be greater than 0";
+        return Err(AppError::new(
+            AppErrorKind::ParseError,
+            error_msg.to_string(),
+        ));
+    }
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => utils::ethereum::get_block_number(&provider).await?,
+    };
+    let contracts = puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address).await?;
+
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block {
+        let chunk_end = to_block.min(chunk_start + chunk_size - 1);
+        let filter = Filter::new()
+            .address(contracts.guardian_module)
+            .topic0(tracked_event_signatures())
+            .from_block(chunk_start)
+            .to_block(chunk_end);
+        let logs = utils::ethereum::get_logs(&provider, &filter).await?;
+
+        for event in logs.iter().filter_map(decode_guardian_log) {
+            if json {
+                let event_json = serde_json::to_string(&event).map_err(|err| {
+                    let error_msg = format!("Failed to serialize event: {err}");
+                    AppError::new(AppErrorKind::ParseError, error_msg)
+                })?;
+                println!("{event_json}");
+            } else {
+                print_event(&event);
+            }
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    if registration_files.is_empty() {
+        return Ok(0);
+    }
+
+    let guardian_module_contract: GuardianModule<_> =
+        GuardianModule::new(contracts.guardian_module, provider.into());
+    let guardian_set = GuardianSet::fetch(&guardian_module_contract).await?;
+    let checks = check_registration_files(
+        registration_files,
+        &guardian_set.enclave_pubkeys,
+        guardian_set.threshold,
+    )?;
+    if json {
+        for check in checks.iter() {
+            let check_json = serde_json::to_string(check).map_err(|err| {
+                let error_msg = format!("Failed to serialize registration file check: {err}");
+                AppError::new(AppErrorKind::ParseError, error_msg)
+            })?;
+            println!("{check_json}");
+        }
+        return Ok(i32::from(checks.iter().any(|check| check.stale)));
+    }
+    Ok(print_registration_file_checks(&checks))
+}
@@ -0,0 +1,220 @@
+pub mod history;
+pub mod show;
+pub mod verify_signatures;
+
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::Serialize;
+
+use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+use coral_lib::strip_0x_prefix;
+
+use crate::commands::resolve;
+use crate::commands::validator::register_key::read_keygen_output;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum GuardiansCommand {
+    #[command(about = "Show the current guardian set of the GuardianModule")]
+    Show {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(
+            long = "registration-file",
+            help = "Keygen output to check against the current guardian set, can be repeated"
+        )]
+        registration_files: Vec<PathBuf>,
+        #[arg(long = "json")]
+        json: bool,
+    },
+    #[command(about = "Show guardian set changes from GuardianModule events")]
+    History {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "from-block", default_value_t = 0)]
+        from_block: u64,
+        #[arg(long = "to-block")]
+        to_block: Option<u64>,
+        #[arg(long = "chunk-size", default_value_t = 2000)]
+        chunk_size: u64,
+        #[arg(
+            long = "registration-file",
+            help = "Keygen output to check against the current guardian set, can be repeated"
+        )]
+        registration_files: Vec<PathBuf>,
+        #[arg(long = "json")]
+        json: bool,
+    },
+    #[command(about = "Check guardian enclave signatures for provisioning a validator")]
+    VerifySignatures {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(
+            long = "index",
+            help = "Validator index in the module, defaults to the next validator to be provisioned"
+        )]
+        index: Option<u64>,
+        #[arg(long = "validator-signature")]
+        validator_signature: String,
+        #[arg(long = "enclave-signature", required = true)]
+        enclave_signatures: Vec<String>,
+        #[arg(long = "json")]
+        json: bool,
+        module_name: String,
+    },
+}
+
+impl GuardiansCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Show {
+                rpc_url,
+                puffer_protocol_address,
+                registration_files,
+                json,
+            } => {
+                show::show_guardians(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &registration_files,
+                    json,
+                )
+                .await
+            }
+            Self::History {
+                rpc_url,
+                puffer_protocol_address,
+                from_block,
+                to_block,
+                chunk_size,
+                registration_files,
+                json,
+            } => {
+                history::guardian_history(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    from_block,
+                    to_block,
+                    chunk_size,
+                    &registration_files,
+                    json,
+                )
+                .await
+            }
+            Self::VerifySignatures {
+                rpc_url,
+                puffer_protocol_address,
+                index,
+                validator_signature,
+                enclave_signatures,
+                json,
+                module_name,
+            } => {
+                verify_signatures::verify_signatures(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &module_name,
+                    index,
+                    &validator_signature,
+                    &enclave_signatures,
+                    json,
+                )
+                .await
+            }
+        }
+    }
+}
+
+fn normalize_pubkey(pub_key: &str) -> String {
+    strip_0x_prefix(pub_key).to_lowercase()
+}
+
+/// Why a registration file no longer matches the current guardian set, if it does not.
+/// Key shares are encrypted per guardian in order, so the order matters too.
+pub fn stale_reason(
+    registration_file: &Path,
+    enclave_pubkeys: &[Bytes],
+    threshold: U256,
+) -> AppResult<Option<String>> {
+    let keygen_data = read_keygen_output(registration_file)?;
+    let file_pubkeys: Vec<String> = keygen_data
+        .guardian_pubkeys
+        .iter()
+        .map(|pub_key| normalize_pubkey(pub_key))
+        .collect();
+    let current_pubkeys: Vec<String> = enclave_pubkeys.iter().map(hex::encode).collect();
+
+    if file_pubkeys != current_pubkeys {
+        let missing = file_pubkeys
+            .iter()
+            .filter(|pub_key| !current_pubkeys.contains(pub_key))
+            .count();
+        let reason = if missing == 0 && file_pubkeys.len() == current_pubkeys.len() {
+            "guardian enclave keys are in a different order".to_string()
+        } else {
+            format!(
+                "encrypted to {} guardian key(s), {missing} of which are no longer current, the set has {}",
+                file_pubkeys.len(),
+                current_pubkeys.len()
+            )
+        };
+        return Ok(Some(reason));
+    }
+    if U256::from(keygen_data.guardian_threshold) != threshold {
+        return Ok(Some(format!(
+            "guardian threshold is {} but the file has {}",
+            threshold, keygen_data.guardian_threshold
+        )));
+    }
+    Ok(None)
+}
+
+/// Result of checking one registration file against the current guardian set
+#[derive(Clone, Debug, Serialize)]
+pub struct RegistrationFileCheck {
+    pub path: PathBuf,
+    pub stale: bool,
+    pub reason: Option<String>,
+}
+
+pub fn check_registration_files(
+    registration_files: &[PathBuf],
+    enclave_pubkeys: &[Bytes],
+    threshold: U256,
+) -> AppResult<Vec<RegistrationFileCheck>> {
+    registration_files
+        .iter()
+        .map(|path| {
+            let reason = stale_reason(path, enclave_pubkeys, threshold)?;
+            Ok(RegistrationFileCheck {
+                path: path.clone(),
+                stale: reason.is_some(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Print whether each registration file matches the current guardian set.
+/// Returns 1 if any of them is stale.
+pub fn print_registration_file_checks(checks: &[RegistrationFileCheck]) -> i32 {
+    for check in checks {
+        match &check.reason {
+            Some(reason) => println!("STALE {}: {reason}", check.path.display()),
+            None => println!("OK {}", check.path.display()),
+        }
+    }
+    if checks.iter().any(|check| check.stale) {
+        return 1;
+    }
+    0
+}
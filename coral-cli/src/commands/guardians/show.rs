@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::fallback_client::FallbackClient;
+use coral_lib::utils::multicall::{BatchReader, CallBatch};
+
+use crate::commands::contracts::puffer_contracts;
+use crate::GuardianModule;
+
+use super::{check_registration_files, print_registration_file_checks, RegistrationFileCheck};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GuardianInfo {
+    pub address: Address,
+    pub enclave_address: Option<Address>,
+    pub enclave_pubkey: Option<String>,
+}
+
+/// Current guardian set of the GuardianModule
+#[derive(Clone, Debug)]
+pub struct GuardianSet {
+    pub guardian_module: Address,
+    pub guardians: Vec<Address>,
+    pub enclave_addresses: Vec<Address>,
+    pub enclave_pubkeys: Vec<Bytes>,
+    pub threshold: U256,
+    pub ejection_threshold: U256,
+    pub mrenclave: [u8; 32],
+    pub mrsigner: [u8; 32],
+}
+
+/// Thresholds are decimal strings
+#[derive(Clone, Debug, Serialize)]
+pub struct GuardianSetOutput {
+    pub guardian_module: Address,
+    pub guardians: Vec<GuardianInfo>,
+    pub threshold: String,
+    pub ejection_threshold: String,
+    pub mrenclave: String,
+    pub mrsigner: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub registration_files: Vec<RegistrationFileCheck>,
+}
+
+impl GuardianSet {
+    pub async fn fetch<M: Middleware<Provider = FallbackClient> + 'static>(
+        guardian_module_contract: &GuardianModule<M>,
+    ) -> AppResult<Self> {
+        let batch_reader = BatchReader::new(guardian_module_contract.client()).await?;
+        let mut batch = CallBatch::new();
+        let guardians = batch.add(guardian_module_contract.get_guardians());
+        let enclave_addresses =
+            batch.add(guardian_module_contract.get_guardians_enclave_addresses());
+        let enclave_pubkeys = batch.add(guardian_module_contract.get_guardians_enclave_pubkeys());
+        let threshold = batch.add(guardian_module_contract.get_threshold());
+        let ejection_threshold = batch.add(guardian_module_contract.get_ejection_threshold());
+        let mrenclave = batch.add(guardian_module_contract.get_mrenclave());
+        let mrsigner = batch.add(guardian_module_contract.get_mrsigner());
+
+        let results = batch_reader.run(batch).await?;
+        Ok(Self {
+            guardian_module: guardian_module_contract.address(),
+            guardians: results.get(guardians)?,
+            enclave_addresses: results.get(enclave_addresses)?,
+            enclave_pubkeys: results.get(enclave_pubkeys)?,
+            threshold: results.get(threshold)?,
+            ejection_threshold: results.get(ejection_threshold)?,
+            mrenclave: results.get(mrenclave)?,
+            mrsigner: results.get(mrsigner)?,
+        })
+    }
+
+    pub fn to_output(&self) -> GuardianSetOutput {
+        // guardians without a registered enclave key have empty entries
+        let guardians = self
+            .guardians
+            .iter()
+            .enumerate()
+            .map(|(i, address)| GuardianInfo {
+                address: *address,
+                enclave_address: self
+                    .enclave_addresses
+                    .get(i)
+                    .filter(|address| !address.is_zero())
+                    .copied(),
+                enclave_pubkey: self
+                    .enclave_pubkeys
+                    .get(i)
+                    .filter(|pub_key| !pub_key.is_empty())
+                    .map(|pub_key| format!("0x{}", hex::encode(pub_key))),
+            })
+            .collect();
+
+        GuardianSetOutput {
+            guardian_module: self.guardian_module,
+            guardians,
+            threshold: self.threshold.to_string(),
+            ejection_threshold: self.ejection_threshold.to_string(),
+            mrenclave: format!("0x{}", hex::encode(self.mrenclave)),
+            mrsigner: format!("0x{}", hex::encode(self.mrsigner)),
+            registration_files: Vec::new(),
+        }
+    }
+}
+
+pub async fn show_guardians(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    registration_files: &[PathBuf],
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let contracts = puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address).await?;
+    let guardian_module_contract: GuardianModule<_> =
+        GuardianModule::new(contracts.guardian_module, provider.into());
+
+    let guardian_set = GuardianSet::fetch(&guardian_module_contract).await?;
+    let mut output = guardian_set.to_output();
+    output.registration_files = check_registration_files(
+        registration_files,
+        &guardian_set.enclave_pubkeys,
+        guardian_set.threshold,
+    )?;
+
+    if json {
+        let guardian_set_json = serde_json::to_string_pretty(&output).map_err(|err| {
+            let error_msg = format!("Failed to serialize guardian set: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{guardian_set_json}");
+        let any_stale = output.registration_files.iter().any(|check| check.stale);
+        return Ok(i32::from(any_stale));
+    }
+
+    println!("GuardianModule: {:?}", output.guardian_module);
+    println!(
+        "Threshold: {} of {}",
+        output.threshold,
+        output.guardians.len()
+    );
+    println!("Ejection threshold: {}", output.ejection_threshold);
+    println!("MRENCLAVE: {}", output.mrenclave);
+    println!("MRSIGNER: {}", output.mrsigner);
+    for (i, guardian) in output.guardians.iter().enumerate() {
+        println!("Guardian {i}: {:?}", guardian.address);
+        match guardian.enclave_address {
+            Some(enclave_address) => println!("  enclave address: {enclave_address:?}"),
+            None => println!("  enclave address: none"),
+        }
+        println!(
+            "  enclave public key: {}",
+            guardian.enclave_pubkey.as_deref().unwrap_or("none")
+        );
+    }
+
+    Ok(print_registration_file_checks(&output.registration_files))
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use ethers::prelude::*;
+use ethers::utils::hex;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::strip_0x_prefix;
+use coral_lib::structs::ModuleName;
+use coral_lib::utils;
+use coral_lib::utils::guardian_signatures::{
+    verify_provisioning_signatures, GuardianAddresses, ProvisioningPayload, ProvisioningValidator,
+    SignatureReport,
+};
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::{GuardianModule, PufferProtocol};
+
+fn parse_hex_bytes(name: &str, value: &str) -> AppResult<Bytes> {
+    let bytes = hex::decode(strip_0x_prefix(value)).map_err(|err| {
+        let error_msg = format!("Failed to decode {name} '{value}': {err}");
+        AppError::new(AppErrorKind::DecodeError, error_msg)
+    })?;
+    Ok(Bytes::from(bytes))
+}
+
+fn print_report(report: &SignatureReport) {
+    println!("Signed message hash: {:?}", report.signed_message_hash);
+    for signature in report.signatures.iter() {
+        match (&signature.signer, &signature.role, &signature.error) {
+            (Some(signer), Some(role), _) => {
+                let duplicate = if signature.duplicate {
+                    " (duplicate)"
+                } else {
+                    ""
+                };
+                println!(
+                    "Signature {}: {signer:?} {role:?}{duplicate}",
+                    signature.index
+                );
+            }
+            (_, _, Some(err)) => println!("Signature {}: invalid: {err}", signature.index),
+            _ => println!("Signature {}: invalid", signature.index),
+        }
+    }
+    println!(
+        "Valid {:?} signatures: {} of threshold {}",
+        report.expected_role, report.valid_signatures, report.threshold
+    );
+    for warning in report.warnings.iter() {
+        println!("WARNING: {warning}");
+    }
+}
+
+pub async fn verify_signatures(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    module_name: &str,
+    index: Option<u64>,
+    validator_signature: &str,
+    enclave_signatures: &[String],
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let module_name = ModuleName::parse(module_name)?;
+    let module_name_bytes = module_name.to_bytes();
+    let validator_signature = parse_hex_bytes("validator signature", validator_signature)?;
+    let enclave_signatures = enclave_signatures
+        .iter()
+        .map(|signature| parse_hex_bytes("enclave signature", signature))
+        .collect::<AppResult<Vec<Bytes>>>()?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let contracts = puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address).await?;
+    let provider = Arc::new(provider);
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address, provider.clone());
+    let (guardian_enclave_pubkeys, withdrawal_credentials, threshold, eth_amount) =
+        puffer_protocol_contract
+            .get_payload(module_name_bytes, true)
+            .await
+            .map_err(contract_call_error("payload"))?;
+    let payload = ProvisioningPayload {
+        guardian_enclave_pubkeys,
+        withdrawal_credentials,
+        threshold,
+        eth_amount,
+    };
+
+    let index = match index {
+        Some(index) => U256::from(index),
+        None => puffer_protocol_contract
+            .get_next_validator_to_be_provisioned_index(module_name_bytes)
+            .await
+            .map_err(contract_call_error(
+                "next validator to be provisioned index",
+            ))?,
+    };
+    let validator = puffer_protocol_contract
+        .get_validator_info(module_name_bytes, index)
+        .await
+        .map_err(contract_call_error("validator info"))?;
+    let deposit_data_root = puffer_protocol_contract
+        .get_deposit_data_root(
+            validator.pub_key.clone(),
+            validator_signature.clone(),
+            payload.withdrawal_credentials.clone(),
+        )
+        .await
+        .map_err(contract_call_error("deposit data root"))?;
+
+    let guardian_module_contract: GuardianModule<_> =
+        GuardianModule::new(contracts.guardian_module, provider);
+    let guardian_addresses = GuardianAddresses {
+        guardians: guardian_module_contract
+            .get_guardians()
+            .await
+            .map_err(contract_call_error("guardians"))?,
+        enclave_addresses: guardian_module_contract
+            .get_guardians_enclave_addresses()
+            .await
+            .map_err(contract_call_error("guardian enclave addresses"))?,
+        threshold: guardian_module_contract
+            .get_threshold()
+            .await
+            .map_err(contract_call_error("guardian threshold"))?,
+    };
+
+    let provisioning_validator = ProvisioningValidator {
+        pub_key: validator.pub_key,
+        signature: validator_signature,
+        deposit_data_root,
+        puffer_module_index: index,
+    };
+    let report = verify_provisioning_signatures(
+        &payload,
+        &provisioning_validator,
+        &enclave_signatures,
+        &guardian_addresses,
+    );
+
+    if json {
+        let report_json = serde_json::to_string_pretty(&report).map_err(|err| {
+            let error_msg = format!("Failed to serialize signature report: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{report_json}");
+    } else {
+        println!("Module: {module_name} index {index}");
+        println!(
+            "Validator: 0x{}",
+            hex::encode(&provisioning_validator.pub_key)
+        );
+        print_report(&report);
+    }
+
+    report.ensure_valid()?;
+    Ok(0)
+}
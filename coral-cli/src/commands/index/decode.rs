@@ -0,0 +1,218 @@
+use ethers::abi::RawLog;
+use ethers::prelude::*;
+use ethers::utils::hex;
+
+use coral_lib::local_db::ProtocolEvent;
+use coral_lib::structs::ModuleName;
+
+use crate::{
+    PufferProtocolEvents, SuccessfullyProvisionedFilter, ValidatorExitedFilter,
+    ValidatorKeyRegisteredFilter, ValidatorSkippedFilter, ValidatorTicketsDepositedFilter,
+    ValidatorTicketsWithdrawnFilter,
+};
+
+/// Signatures of the PufferProtocol events coral tracks
+pub fn tracked_event_signatures() -> Vec<H256> {
+    vec![
+        ValidatorKeyRegisteredFilter::signature(),
+        SuccessfullyProvisionedFilter::signature(),
+        ValidatorSkippedFilter::signature(),
+        ValidatorExitedFilter::signature(),
+        ValidatorTicketsDepositedFilter::signature(),
+        ValidatorTicketsWithdrawnFilter::signature(),
+    ]
+}
+
+/// `None` if the puffer module index is out of range, such a log is skipped
+fn validator_event(
+    log: &Log,
+    event: &str,
+    pub_key: &Bytes,
+    puffer_module_index: U256,
+    module_name: [u8; 32],
+) -> Option<ProtocolEvent> {
+    let Ok(puffer_module_index) = u64::try_from(puffer_module_index) else {
+        eprintln!(
+            "Skipping {event} log {:?}:{}, invalid puffer module index {puffer_module_index}",
+            log.transaction_hash.unwrap_or_default(),
+            log.log_index.unwrap_or_default()
+        );
+        return None;
+    };
+    Some(ProtocolEvent {
+        module_name: Some(ModuleName::new(module_name).to_string()),
+        puffer_module_index: Some(puffer_module_index),
+        pub_key: Some(format!("0x{}", hex::encode(pub_key))),
+        ..empty_event(log, event)
+    })
+}
+
+fn empty_event(log: &Log, event: &str) -> ProtocolEvent {
+    ProtocolEvent {
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+        log_index: log.log_index.unwrap_or_default().as_u64(),
+        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+        event: event.to_string(),
+        module_name: None,
+        puffer_module_index: None,
+        pub_key: None,
+        node: None,
+        amount: None,
+    }
+}
+
+/// Decode a tracked PufferProtocol log.
+/// `node` is only known from the log for ticket deposits and withdrawals,
+/// for exits `amount` is the VT burned.
+pub fn decode_protocol_log(log: &Log) -> Option<ProtocolEvent> {
+    let raw_log = RawLog::from(log.clone());
+    let event = match PufferProtocolEvents::decode_log(&raw_log).ok()? {
+        PufferProtocolEvents::ValidatorKeyRegisteredFilter(event) => validator_event(
+            log,
+            "ValidatorKeyRegistered",
+            &event.pub_key,
+            event.puffer_module_index,
+            event.module_name,
+        )?,
+        PufferProtocolEvents::SuccessfullyProvisionedFilter(event) => validator_event(
+            log,
+            "SuccessfullyProvisioned",
+            &event.pub_key,
+            event.puffer_module_index,
+            event.module_name,
+        )?,
+        PufferProtocolEvents::ValidatorSkippedFilter(event) => validator_event(
+            log,
+            "ValidatorSkipped",
+            &event.pub_key,
+            event.puffer_module_index,
+            event.module_name,
+        )?,
+        PufferProtocolEvents::ValidatorExitedFilter(event) => ProtocolEvent {
+            amount: Some(event.vt_burn_amount.to_string()),
+            ..validator_event(
+                log,
+                "ValidatorExited",
+                &event.pub_key,
+                event.puffer_module_index,
+                event.module_name,
+            )?
+        },
+        PufferProtocolEvents::ValidatorTicketsDepositedFilter(event) => ProtocolEvent {
+            node: Some(format!("{:?}", event.node)),
+            amount: Some(event.amount.to_string()),
+            ..empty_event(log, "ValidatorTicketsDeposited")
+        },
+        PufferProtocolEvents::ValidatorTicketsWithdrawnFilter(event) => ProtocolEvent {
+            node: Some(format!("{:?}", event.node)),
+            amount: Some(event.amount.to_string()),
+            ..empty_event(log, "ValidatorTicketsWithdrawn")
+        },
+        _ => return None,
+    };
+    Some(event)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use ethers::abi::{encode, Token};
+
+    use super::*;
+
+    pub const MODULE_NAME: &str = "PUFFER_MODULE_0";
+
+    pub fn log(topics: Vec<H256>, data: Vec<Token>, tx_hash: H256) -> Log {
+        Log {
+            topics,
+            data: encode(&data).into(),
+            block_number: Some(19512345.into()),
+            log_index: Some(42.into()),
+            transaction_hash: Some(tx_hash),
+            ..Default::default()
+        }
+    }
+
+    pub fn registered_log(pub_key: &[u8], puffer_module_index: U256, tx_hash: H256) -> Log {
+        let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+        log(
+            vec![
+                ValidatorKeyRegisteredFilter::signature(),
+                H256::from_uint(&puffer_module_index),
+                H256::from(module_name),
+            ],
+            vec![Token::Bytes(pub_key.to_vec()), Token::Bool(true)],
+            tx_hash,
+        )
+    }
+
+    pub fn provisioned_log(pub_key: &[u8], puffer_module_index: U256) -> Log {
+        let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+        log(
+            vec![
+                SuccessfullyProvisionedFilter::signature(),
+                H256::from_uint(&puffer_module_index),
+                H256::from(module_name),
+            ],
+            vec![Token::Bytes(pub_key.to_vec())],
+            H256::repeat_byte(0x22),
+        )
+    }
+
+    pub fn vt_deposited_log(node: Address, amount: U256) -> Log {
+        log(
+            vec![
+                ValidatorTicketsDepositedFilter::signature(),
+                H256::from(node),
+                H256::from(Address::repeat_byte(0x33)),
+            ],
+            vec![Token::Uint(amount)],
+            H256::repeat_byte(0x44),
+        )
+    }
+
+    #[test]
+    fn decodes_validator_event() {
+        let tx_hash = H256::repeat_byte(0x11);
+        let log = registered_log(&[0xab; 48], U256::from(17), tx_hash);
+
+        let event = decode_protocol_log(&log).unwrap();
+        assert_eq!(
+            event,
+            ProtocolEvent {
+                block_number: 19512345,
+                log_index: 42,
+                tx_hash: format!("{tx_hash:?}"),
+                event: "ValidatorKeyRegistered".to_string(),
+                module_name: Some(MODULE_NAME.to_string()),
+                puffer_module_index: Some(17),
+                pub_key: Some(format!("0x{}", "ab".repeat(48))),
+                node: None,
+                amount: None,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_ticket_event() {
+        let node = Address::repeat_byte(0x2a);
+        let log = vt_deposited_log(node, U256::exp10(18));
+
+        let event = decode_protocol_log(&log).unwrap();
+        assert_eq!(event.event, "ValidatorTicketsDeposited");
+        assert_eq!(event.node, Some(format!("{node:?}")));
+        assert_eq!(event.amount, Some("1000000000000000000".to_string()));
+        assert_eq!(event.pub_key, None);
+    }
+
+    #[test]
+    fn skips_out_of_range_module_index() {
+        let log = provisioned_log(&[0xab; 48], U256::from(u64::MAX) + 1);
+        assert_eq!(decode_protocol_log(&log), None);
+    }
+
+    #[test]
+    fn skips_untracked_log() {
+        let log = log(vec![H256::repeat_byte(0x55)], vec![], H256::zero());
+        assert_eq!(decode_protocol_log(&log), None);
+    }
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use coral_lib::error::AppResult;
+use coral_lib::local_db::LocalDb;
+use coral_lib::utils;
+
+/// Print indexed events as JSON lines
+pub fn list_events(db_path: &Path, pub_key: Option<&str>, node: Option<&str>) -> AppResult<i32> {
+    let node = node
+        .map(|node| utils::ethereum::parse_address("node", node).map(|node| format!("{node:?}")))
+        .transpose()?;
+    let pub_key = pub_key.map(|pub_key| coral_lib::add_0x_prefix(&pub_key.to_lowercase()));
+
+    let db = LocalDb::open(db_path)?;
+    for event in db.get_events(pub_key.as_deref(), node.as_deref())? {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    Ok(0)
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers::prelude::*;
+
+use coral_lib::error::AppResult;
+use coral_lib::local_db::ProtocolEvent;
+use coral_lib::utils;
+
+use super::decode::decode_protocol_log;
+
+/// Keeps the PufferProtocol events that belong to our nodes or validators
+#[derive(Clone, Debug, Default)]
+pub struct EventMatcher {
+    nodes: HashSet<String>,
+    /// pubkey -> registering node, if known
+    registered: HashMap<String, Option<String>>,
+}
+
+impl EventMatcher {
+    pub fn new(nodes: &[Address]) -> Self {
+        Self {
+            nodes: nodes.iter().map(|node| format!("{node:?}")).collect(),
+            registered: HashMap::new(),
+        }
+    }
+
+    pub fn add_pubkey(&mut self, pub_key: String, node: Option<String>) {
+        self.registered.insert(pub_key, node);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.registered.is_empty()
+    }
+
+    /// Decode `log` and return it if it is one of ours.
+    /// Registrations by our nodes add the key to the set of tracked validators.
+    pub async fn match_log<J, E>(
+        &mut self,
+        provider: &Provider<J>,
+        log: &Log,
+    ) -> AppResult<Option<ProtocolEvent>>
+    where
+        J: JsonRpcClient<Error = E>,
+    {
+        let Some(mut event) = decode_protocol_log(log) else {
+            return Ok(None);
+        };
+        match (event.event.as_str(), event.pub_key.as_ref()) {
+            ("ValidatorKeyRegistered", Some(pub_key)) => {
+                if let Some(node) = self.registered.get(pub_key) {
+                    event.node = node.clone();
+                } else {
+                    if self.nodes.is_empty() {
+                        return Ok(None);
+                    }
+                    // the registering node is the sender of the transaction
+                    let tx_hash = log.transaction_hash.unwrap_or_default();
+                    let tx = utils::ethereum::get_transaction(provider, tx_hash).await?;
+                    let node = format!("{:?}", tx.from);
+                    if !self.nodes.contains(&node) {
+                        return Ok(None);
+                    }
+                    self.registered.insert(pub_key.clone(), Some(node.clone()));
+                    event.node = Some(node);
+                }
+            }
+            (_, Some(pub_key)) => match self.registered.get(pub_key) {
+                Some(node) => event.node = node.clone(),
+                None => return Ok(None),
+            },
+            (_, None) => {
+                if !event
+                    .node
+                    .as_ref()
+                    .is_some_and(|node| self.nodes.contains(node))
+                {
+                    return Ok(None);
+                }
+            }
+        }
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use coral_lib::local_db::LocalDb;
+
+    use super::super::decode::tests::{provisioned_log, registered_log, vt_deposited_log};
+    use super::*;
+
+    const NODE: Address = H160([0x2a; 20]);
+    const OTHER_NODE: Address = H160([0x8c; 20]);
+
+    /// Provider answering `eth_getTransactionByHash` with a transaction sent by `from`
+    fn provider_with_sender(tx_hash: H256, from: Address) -> Provider<MockProvider> {
+        let (provider, mock) = Provider::mocked();
+        let tx = Transaction {
+            hash: tx_hash,
+            from,
+            ..Default::default()
+        };
+        mock.push::<Transaction, _>(tx).unwrap();
+        provider
+    }
+
+    #[tokio::test]
+    async fn tracks_keys_registered_by_node() {
+        let tx_hash = H256::repeat_byte(0x11);
+        let provider = provider_with_sender(tx_hash, NODE);
+        let mut matcher = EventMatcher::new(&[NODE]);
+
+        let log = registered_log(&[0xab; 48], U256::from(3), tx_hash);
+        let event = matcher.match_log(&provider, &log).await.unwrap().unwrap();
+        assert_eq!(event.node, Some(format!("{NODE:?}")));
+
+        // the key is known now, no request needed
+        let log = provisioned_log(&[0xab; 48], U256::from(3));
+        let event = matcher.match_log(&provider, &log).await.unwrap().unwrap();
+        assert_eq!(event.event, "SuccessfullyProvisioned");
+        assert_eq!(event.node, Some(format!("{NODE:?}")));
+    }
+
+    #[tokio::test]
+    async fn ignores_keys_of_other_nodes() {
+        let tx_hash = H256::repeat_byte(0x11);
+        let provider = provider_with_sender(tx_hash, OTHER_NODE);
+        let mut matcher = EventMatcher::new(&[NODE]);
+
+        let log = registered_log(&[0xab; 48], U256::from(3), tx_hash);
+        assert_eq!(matcher.match_log(&provider, &log).await.unwrap(), None);
+        let log = provisioned_log(&[0xab; 48], U256::from(3));
+        assert_eq!(matcher.match_log(&provider, &log).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn matches_ticket_events_of_nodes() {
+        let (provider, _) = Provider::mocked();
+        let mut matcher = EventMatcher::new(&[NODE]);
+
+        let log = vt_deposited_log(NODE, U256::from(100));
+        assert!(matcher.match_log(&provider, &log).await.unwrap().is_some());
+        let log = vt_deposited_log(OTHER_NODE, U256::from(100));
+        assert_eq!(matcher.match_log(&provider, &log).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn tracks_keys_from_db() {
+        let tx_hash = H256::repeat_byte(0x11);
+        let provider = provider_with_sender(tx_hash, NODE);
+        let mut matcher = EventMatcher::new(&[NODE]);
+        let log = registered_log(&[0xab; 48], U256::from(3), tx_hash);
+        let event = matcher.match_log(&provider, &log).await.unwrap().unwrap();
+
+        let mut db = LocalDb::open(Path::new(":memory:")).unwrap();
+        db.insert_events(&[event], "index", 19512345).unwrap();
+
+        // a matcher without nodes, e.g. `watch --bls-public-key`, tracks the stored keys
+        let (provider, _) = Provider::mocked();
+        let mut matcher = EventMatcher::new(&[]);
+        for (pub_key, node) in db.get_registered_pubkeys().unwrap() {
+            matcher.add_pubkey(pub_key, Some(node));
+        }
+        let log = provisioned_log(&[0xab; 48], U256::from(3));
+        let event = matcher.match_log(&provider, &log).await.unwrap().unwrap();
+        assert_eq!(event.node, Some(format!("{NODE:?}")));
+        let log = provisioned_log(&[0xcd; 48], U256::from(4));
+        assert_eq!(matcher.match_log(&provider, &log).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,82 @@
+pub mod decode;
+pub mod events;
+pub mod matcher;
+pub mod sync;
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum IndexCommand {
+    #[command(about = "Index PufferProtocol events of our nodes into a local database")]
+    Sync {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "node", required = true)]
+        nodes: Vec<String>,
+        #[arg(long = "db", default_value = "coral-index.sqlite")]
+        db: PathBuf,
+        #[arg(long = "from-block", default_value_t = 0)]
+        from_block: u64,
+        #[arg(long = "to-block")]
+        to_block: Option<u64>,
+        #[arg(long = "confirmations", default_value_t = 2)]
+        confirmations: u64,
+        #[arg(long = "chunk-size", default_value_t = 2000)]
+        chunk_size: u64,
+    },
+    #[command(about = "List indexed events as JSON lines")]
+    Events {
+        #[arg(long = "db", default_value = "coral-index.sqlite")]
+        db: PathBuf,
+        #[arg(long = "bls-public-key")]
+        bls_pubkey: Option<String>,
+        #[arg(long = "node")]
+        node: Option<String>,
+    },
+}
+
+impl IndexCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Sync {
+                rpc_url,
+                puffer_protocol_address,
+                nodes,
+                db,
+                from_block,
+                to_block,
+                confirmations,
+                chunk_size,
+            } => {
+                sync::sync_events(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &nodes,
+                    db.as_path(),
+                    from_block,
+                    to_block,
+                    confirmations,
+                    chunk_size,
+                )
+                .await?;
+            }
+            Self::Events {
+                db,
+                bls_pubkey,
+                node,
+            } => {
+                events::list_events(db.as_path(), bls_pubkey.as_deref(), node.as_deref())?;
+            }
+        }
+        Ok(0)
+    }
+}
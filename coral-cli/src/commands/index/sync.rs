@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use ethers::prelude::*;
+use ethers::utils::{hex, keccak256};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::local_db::{LocalDb, ProtocolEvent};
+use coral_lib::utils;
+
+use super::decode::tracked_event_signatures;
+use super::matcher::EventMatcher;
+
+/// Progress key for syncing the events of `nodes` on a PufferProtocol deployment from `from_block`.
+/// Another start or node set is synced from scratch, events already stored are kept.
+pub fn sync_progress_name(
+    chain_id: U256,
+    puffer_protocol_address: Address,
+    from_block: u64,
+    nodes: &[Address],
+) -> String {
+    let mut nodes: Vec<String> = nodes.iter().map(|node| format!("{node:?}")).collect();
+    nodes.sort();
+    nodes.dedup();
+    let nodes_hash = keccak256(nodes.join(","));
+    format!(
+        "index:{chain_id}:{puffer_protocol_address:?}:{from_block}:{}",
+        hex::encode(&nodes_hash[..8])
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_events(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    nodes: &[String],
+    db_path: &Path,
+    from_block: u64,
+    to_block: Option<u64>,
+    confirmations: u64,
+    chunk_size: u64,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let nodes = nodes
+        .iter()
+        .map(|node| utils::ethereum::parse_address("node", node))
+        .collect::<Result<Vec<Address>, _>>()?;
+    if chunk_size == 0 {
+        let error_msg = "Chunk size must be greater than 0";
+        return Err(AppError::new(
+            AppErrorKind::ParseError,
+            error_msg.to_string(),
+        ));
+    }
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => {
+            let latest = utils::ethereum::get_block_number(&provider).await?;
+            latest.saturating_sub(confirmations)
+        }
+    };
+
+    let mut db = LocalDb::open(db_path)?;
+    let progress_name =
+        sync_progress_name(chain_id, puffer_protocol_address_h160, from_block, &nodes);
+    let start_block = match db.get_progress(&progress_name)? {
+        Some(last_block) => {
+            println!("Resuming after block {last_block}");
+            last_block + 1
+        }
+        _ => from_block,
+    };
+    let mut matcher = EventMatcher::new(&nodes);
+    for (pub_key, node) in db.get_registered_pubkeys()? {
+        matcher.add_pubkey(pub_key, Some(node));
+    }
+
+    let mut total_events = 0;
+    let mut chunk_start = start_block;
+    while chunk_start <= to_block {
+        let chunk_end = to_block.min(chunk_start + chunk_size - 1);
+        let filter = Filter::new()
+            .address(puffer_protocol_address_h160)
+            .topic0(tracked_event_signatures())
+            .from_block(chunk_start)
+            .to_block(chunk_end);
+        let logs = utils::ethereum::get_logs(&provider, &filter).await?;
+
+        let mut events: Vec<ProtocolEvent> = Vec::new();
+        for log in logs.iter() {
+            if let Some(event) = matcher.match_log(&provider, log).await? {
+                events.push(event);
+            }
+        }
+
+        db.insert_events(&events, &progress_name, chunk_end)?;
+        total_events += events.len();
+        println!(
+            "Synced blocks {chunk_start}..={chunk_end}: {} event(s)",
+            events.len()
+        );
+        chunk_start = chunk_end + 1;
+    }
+
+    println!("Done, {total_events} new event(s) up to block {to_block}");
+    Ok(0)
+}
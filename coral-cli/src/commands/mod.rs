@@ -1,12 +1,62 @@
+#[cfg(feature = "dev")]
+mod contracts;
+#[cfg(feature = "dev")]
+mod dev;
+#[cfg(feature = "dev")]
+mod guardians;
+#[cfg(feature = "dev")]
+mod index;
+#[cfg(feature = "dev")]
+mod module;
+#[cfg(feature = "dev")]
+mod node;
+#[cfg(feature = "dev")]
+mod protocol;
+mod resolve;
+#[cfg(feature = "dev")]
+pub mod tx;
 mod validator;
+#[cfg(feature = "dev")]
+mod vault;
+#[cfg(feature = "dev")]
+mod vt;
+#[cfg(feature = "dev")]
+mod watch;
+
+use std::path::PathBuf;
 
 use clap::{CommandFactory, Parser, Subcommand};
 
 use coral_lib::error::AppResult;
+#[cfg(feature = "dev")]
+use coral_lib::error::{AppError, AppErrorKind};
+use coral_lib::profile::Profile;
 
 use crate::print_version;
 
+#[cfg(feature = "dev")]
+use self::contracts::ContractsCommand;
+#[cfg(feature = "dev")]
+use self::dev::DevCommand;
+#[cfg(feature = "dev")]
+use self::guardians::GuardiansCommand;
+#[cfg(feature = "dev")]
+use self::index::IndexCommand;
+#[cfg(feature = "dev")]
+use self::module::ModuleCommand;
+#[cfg(feature = "dev")]
+use self::node::NodeCommand;
+#[cfg(feature = "dev")]
+use self::protocol::ProtocolCommand;
+#[cfg(feature = "dev")]
+use self::tx::TxCommand;
 use self::validator::ValidatorCommand;
+#[cfg(feature = "dev")]
+use self::vault::VaultCommand;
+#[cfg(feature = "dev")]
+use self::vt::VtCommand;
+#[cfg(feature = "dev")]
+use self::watch::WatchArgs;
 
 #[derive(Clone, Debug, Parser)]
 #[command(author, about, arg_required_else_help(true))]
@@ -16,6 +66,36 @@ pub struct CommandArgs {
 
     #[arg(short = 'v', long = "version")]
     pub version: bool,
+
+    #[arg(
+        long = "profile",
+        global = true,
+        help = "Network profile providing defaults for RPC, enclave, contract and fork flags"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        long = "profiles-file",
+        global = true,
+        help = "Profiles file (default: $CORAL_PROFILES or ~/.coral/profiles.json)"
+    )]
+    pub profiles_file: Option<PathBuf>,
+
+    #[cfg(feature = "dev")]
+    #[arg(
+        long = "rpc-timeout",
+        global = true,
+        help = "Timeout of each RPC request in seconds (default: $CORAL_RPC_TIMEOUT or 30)"
+    )]
+    pub rpc_timeout: Option<u64>,
+
+    #[cfg(feature = "dev")]
+    #[arg(
+        long = "rpc-max-retries",
+        global = true,
+        help = "Rounds over all RPC endpoints after the first one (default: $CORAL_RPC_MAX_RETRIES or 3)"
+    )]
+    pub rpc_max_retries: Option<u32>,
 }
 
 #[derive(Clone, Debug, Subcommand)]
@@ -23,14 +103,84 @@ pub enum SubCommand {
     #[command(about = "Show shell completions")]
     Completions { shell: clap_complete::Shell },
 
+    #[cfg(feature = "dev")]
+    #[command(about = "Show the contract addresses of a PufferProtocol deployment")]
+    Contracts {
+        #[command(subcommand)]
+        command: Option<ContractsCommand>,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "refresh", help = "Ignore cached addresses")]
+        refresh: bool,
+        #[arg(long = "json")]
+        json: bool,
+    },
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Development tools", name = "dev", hide = true)]
+    Dev(DevCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Guardian set commands", name = "guardians")]
+    Guardians(GuardiansCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Local event index commands", name = "index")]
+    Index(IndexCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Puffer module commands", name = "module")]
+    Module(ModuleCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Node operator commands", name = "node")]
+    Node(NodeCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Protocol-wide commands", name = "protocol")]
+    Protocol(ProtocolCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Transaction commands", name = "tx")]
+    Tx(TxCommand),
+
     #[command(subcommand, about = "Validator commands", name = "validator")]
     Validator(ValidatorCommand),
 
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "pufETH vault commands", name = "vault")]
+    Vault(VaultCommand),
+
     #[command(about = "Show version")]
     Version,
+
+    #[cfg(feature = "dev")]
+    #[command(subcommand, about = "Validator ticket commands", name = "vt")]
+    Vt(VtCommand),
+
+    #[cfg(feature = "dev")]
+    #[command(about = "Watch for provisioning, skips and exits of our validators")]
+    Watch(WatchArgs),
 }
 
-pub async fn run_command(command: SubCommand) -> AppResult<i32> {
+/// Timeout and retries of every RPC provider, see [`resolve::rpc_config`]
+#[cfg(feature = "dev")]
+pub fn init_rpc_config(timeout: Option<u64>, max_retries: Option<u32>, profile: Option<&Profile>) {
+    resolve::rpc_config(timeout, max_retries, profile).init();
+}
+
+/// Maps a failed contract read to a `ContractCallError` naming what was fetched
+#[cfg(feature = "dev")]
+pub fn contract_call_error<E: std::fmt::Display>(what: &str) -> impl FnOnce(E) -> AppError + '_ {
+    move |err| {
+        let error_msg = format!("Failed to fetch {what}: {err}");
+        AppError::new(AppErrorKind::ContractCallError, error_msg)
+    }
+}
+
+pub async fn run_command(command: SubCommand, profile: Option<&Profile>) -> AppResult<i32> {
     match command {
         SubCommand::Version => {
             print_version();
@@ -42,6 +192,47 @@ pub async fn run_command(command: SubCommand) -> AppResult<i32> {
             clap_complete::generate(shell, &mut app, bin_name, &mut std::io::stdout());
             Ok(0)
         }
-        SubCommand::Validator(subcommand) => subcommand.execute().await,
+        #[cfg(feature = "dev")]
+        SubCommand::Contracts {
+            command: Some(subcommand),
+            ..
+        } => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Contracts {
+            command: None,
+            rpc_url,
+            puffer_protocol_address,
+            refresh,
+            json,
+        } => {
+            contracts::show_contracts(
+                &resolve::rpc_url(rpc_url, profile).await?,
+                &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                refresh,
+                json,
+            )
+            .await
+        }
+        #[cfg(feature = "dev")]
+        SubCommand::Dev(subcommand) => subcommand.execute().await,
+        #[cfg(feature = "dev")]
+        SubCommand::Guardians(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Index(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Module(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Node(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Protocol(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Tx(subcommand) => subcommand.execute(profile).await,
+        SubCommand::Validator(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Vault(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Vt(subcommand) => subcommand.execute(profile).await,
+        #[cfg(feature = "dev")]
+        SubCommand::Watch(args) => watch::watch(args, profile).await,
     }
 }
@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::structs::ModuleName;
+use coral_lib::utils;
+use coral_lib::utils::fallback_client::FallbackClient;
+use coral_lib::utils::multicall::BatchReader;
+
+use crate::commands::contract_call_error;
+use crate::{PufferModule, PufferProtocol};
+
+type Client = SignerMiddleware<Provider<FallbackClient>, LocalWallet>;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ModuleInfo {
+    pub name: ModuleName,
+    pub name_hex: String,
+    /// Number of slots in the module weights
+    pub weight: usize,
+    pub address: Address,
+    pub allowed_limit: u128,
+    pub number_of_registered_validators: u128,
+    pub pending_validator_index: u64,
+    pub next_validator_to_be_provisioned_index: u64,
+    pub withdrawal_credentials: String,
+    pub eigen_pod: Address,
+    pub last_proof_of_rewards_block: u64,
+}
+
+/// Info of the given modules, each field read for all modules in one batch
+async fn fetch_modules_info(
+    puffer_protocol_contract: &PufferProtocol<Client>,
+    client: Arc<Client>,
+    batch_reader: &BatchReader<Client>,
+    modules: &[([u8; 32], usize)],
+) -> AppResult<Vec<ModuleInfo>> {
+    let addresses = batch_reader
+        .call(
+            modules
+                .iter()
+                .map(|(name, _)| puffer_protocol_contract.get_module_address(*name))
+                .collect(),
+        )
+        .await?;
+    if let Some(((name, _), _)) = modules
+        .iter()
+        .zip(addresses.iter())
+        .find(|(_, address)| address.is_zero())
+    {
+        let error_msg = format!("Module '{}' does not exist", ModuleName::new(*name));
+        return Err(AppError::new(AppErrorKind::AppError, error_msg));
+    }
+
+    let limits = batch_reader
+        .call(
+            modules
+                .iter()
+                .map(|(name, _)| puffer_protocol_contract.get_module_limit_information(*name))
+                .collect(),
+        )
+        .await?;
+    let pending_validator_indexes = batch_reader
+        .call(
+            modules
+                .iter()
+                .map(|(name, _)| puffer_protocol_contract.get_pending_validator_index(*name))
+                .collect(),
+        )
+        .await?;
+    let next_validator_to_be_provisioned_indexes = batch_reader
+        .call(
+            modules
+                .iter()
+                .map(|(name, _)| {
+                    puffer_protocol_contract.get_next_validator_to_be_provisioned_index(*name)
+                })
+                .collect(),
+        )
+        .await?;
+    let withdrawal_credentials = batch_reader
+        .call(
+            addresses
+                .iter()
+                .map(|address| puffer_protocol_contract.get_withdrawal_credentials(*address))
+                .collect(),
+        )
+        .await?;
+
+    let puffer_module_contracts: Vec<PufferModule<Client>> = addresses
+        .iter()
+        .map(|address| PufferModule::new(*address, client.clone()))
+        .collect();
+    let eigen_pods = batch_reader
+        .call(
+            puffer_module_contracts
+                .iter()
+                .map(|contract| contract.get_eigen_pod())
+                .collect(),
+        )
+        .await?;
+    let last_proof_of_rewards_blocks = batch_reader
+        .call(
+            puffer_module_contracts
+                .iter()
+                .map(|contract| contract.get_last_proof_of_rewards_block())
+                .collect(),
+        )
+        .await?;
+
+    let modules_info = modules
+        .iter()
+        .enumerate()
+        .map(|(i, (module_name, weight))| ModuleInfo {
+            name: ModuleName::new(*module_name),
+            name_hex: format!("0x{}", hex::encode(module_name)),
+            weight: *weight,
+            address: addresses[i],
+            allowed_limit: limits[i].allowed_limit,
+            number_of_registered_validators: limits[i].number_of_registered_validators,
+            pending_validator_index: pending_validator_indexes[i].as_u64(),
+            next_validator_to_be_provisioned_index: next_validator_to_be_provisioned_indexes[i]
+                .as_u64(),
+            withdrawal_credentials: format!("0x{}", hex::encode(&withdrawal_credentials[i])),
+            eigen_pod: eigen_pods[i],
+            last_proof_of_rewards_block: last_proof_of_rewards_blocks[i].as_u64(),
+        })
+        .collect();
+    Ok(modules_info)
+}
+
+/// Modules in the order they first appear in the weights, with their number of slots
+async fn fetch_module_weights(
+    puffer_protocol_contract: &PufferProtocol<Client>,
+) -> AppResult<Vec<([u8; 32], usize)>> {
+    let module_weights = puffer_protocol_contract
+        .get_module_weights()
+        .await
+        .map_err(contract_call_error("module weights"))?;
+    let mut weights: Vec<([u8; 32], usize)> = Vec::new();
+    for module_name in module_weights {
+        match weights.iter_mut().find(|(name, _)| *name == module_name) {
+            Some((_, weight)) => *weight += 1,
+            None => weights.push((module_name, 1)),
+        }
+    }
+    Ok(weights)
+}
+
+fn print_module_info(module_info: &ModuleInfo) {
+    println!("Module: {} ({})", module_info.name, module_info.name_hex);
+    println!("  Address: {:?}", module_info.address);
+    println!("  Weight: {}", module_info.weight);
+    println!(
+        "  Registered validators: {} / {}",
+        module_info.number_of_registered_validators, module_info.allowed_limit
+    );
+    println!(
+        "  Pending validator index: {}",
+        module_info.pending_validator_index
+    );
+    println!(
+        "  Next validator to be provisioned index: {}",
+        module_info.next_validator_to_be_provisioned_index
+    );
+    println!(
+        "  Withdrawal credentials: {}",
+        module_info.withdrawal_credentials
+    );
+    println!("  EigenPod: {:?}", module_info.eigen_pod);
+    println!(
+        "  Last proof of rewards block: {}",
+        module_info.last_proof_of_rewards_block
+    );
+}
+
+fn print_json<T: Serialize>(value: &T) -> AppResult {
+    let value_json = serde_json::to_string_pretty(value).map_err(|err| {
+        let error_msg = format!("Failed to serialize module info: {err}");
+        AppError::new(AppErrorKind::ParseError, error_msg)
+    })?;
+    println!("{value_json}");
+    Ok(())
+}
+
+async fn get_contract(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+) -> AppResult<(PufferProtocol<Client>, Arc<Client>, BatchReader<Client>)> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::generate_random_wallet();
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+    let batch_reader = BatchReader::new(client.clone()).await?;
+    Ok((puffer_protocol_contract, client, batch_reader))
+}
+
+pub async fn list_modules(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    json: bool,
+) -> AppResult<i32> {
+    let (puffer_protocol_contract, client, batch_reader) =
+        get_contract(rpc_url, puffer_protocol_address).await?;
+
+    let module_weights = fetch_module_weights(&puffer_protocol_contract).await?;
+    let modules = fetch_modules_info(
+        &puffer_protocol_contract,
+        client,
+        &batch_reader,
+        &module_weights,
+    )
+    .await?;
+
+    if json {
+        print_json(&modules)?;
+        return Ok(0);
+    }
+    for module_info in modules.iter() {
+        print_module_info(module_info);
+    }
+    Ok(0)
+}
+
+pub async fn show_module(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    module_name: &str,
+    json: bool,
+) -> AppResult<i32> {
+    let module_name = ModuleName::parse(module_name)?.to_bytes();
+    let (puffer_protocol_contract, client, batch_reader) =
+        get_contract(rpc_url, puffer_protocol_address).await?;
+
+    let weight = fetch_module_weights(&puffer_protocol_contract)
+        .await?
+        .into_iter()
+        .find(|(name, _)| *name == module_name)
+        .map_or(0, |(_, weight)| weight);
+    let module_info = fetch_modules_info(
+        &puffer_protocol_contract,
+        client,
+        &batch_reader,
+        &[(module_name, weight)],
+    )
+    .await?
+    .remove(0);
+
+    if json {
+        print_json(&module_info)?;
+        return Ok(0);
+    }
+    print_module_info(&module_info);
+    Ok(0)
+}
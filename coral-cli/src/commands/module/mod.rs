@@ -0,0 +1,65 @@
+pub mod info;
+
+use clap::Subcommand;
+
+use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ModuleCommand {
+    #[command(about = "List modules in PufferProtocol's module weights")]
+    List {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "json")]
+        json: bool,
+    },
+    #[command(about = "Show a module's address, limits, validators and EigenPod")]
+    Show {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "json")]
+        json: bool,
+        module_name: String,
+    },
+}
+
+impl ModuleCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::List {
+                rpc_url,
+                puffer_protocol_address,
+                json,
+            } => {
+                info::list_modules(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    json,
+                )
+                .await?;
+            }
+            Self::Show {
+                rpc_url,
+                puffer_protocol_address,
+                json,
+                module_name,
+            } => {
+                info::show_module(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &module_name,
+                    json,
+                )
+                .await?;
+            }
+        }
+        Ok(0)
+    }
+}
@@ -0,0 +1,42 @@
+pub mod status;
+
+use clap::Subcommand;
+
+use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum NodeCommand {
+    #[command(about = "Show validators, tickets and balances of a node")]
+    Status {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "node")]
+        node: String,
+        #[arg(long = "json")]
+        json: bool,
+    },
+}
+
+impl NodeCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Status {
+                rpc_url,
+                puffer_protocol_address,
+                node,
+                json,
+            } => {
+                let rpc_url = resolve::rpc_url(rpc_url, profile).await?;
+                let puffer_protocol_address =
+                    resolve::puffer_protocol_address(puffer_protocol_address, profile)?;
+                status::node_status(&rpc_url, &puffer_protocol_address, &node, json).await?;
+            }
+        }
+        Ok(0)
+    }
+}
@@ -0,0 +1,114 @@
+use ethers::prelude::*;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::multicall::{BatchReader, CallBatch};
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::vt::{format_vt, vt_to_days};
+use crate::{PufferProtocol, PufferVault, ValidatorTicket};
+
+/// Amounts are in wei, as decimal strings
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeStatus {
+    pub node: Address,
+    pub active_validator_count: u64,
+    pub pending_validator_count: u64,
+    pub vt_balance: String,
+    pub vt_wallet_balance: String,
+    pub vt_penalty: String,
+    pub minimum_vt_amount: String,
+    pub pufeth_balance: String,
+    pub pufeth_balance_in_eth: String,
+    /// `None` when the node has no active validators
+    pub days_remaining: Option<f64>,
+}
+
+pub async fn node_status(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    node: &str,
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let node = utils::ethereum::parse_address("node", node)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::generate_random_wallet();
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let validator_ticket_contract: ValidatorTicket<_> =
+        ValidatorTicket::new(contracts.validator_ticket, client.clone());
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    let batch_reader = BatchReader::new(client.clone()).await?;
+    let mut batch = CallBatch::new();
+    let node_info = batch.add(puffer_protocol_contract.get_node_info(node));
+    let vt_penalty = batch.add(puffer_protocol_contract.get_vt_penalty());
+    let minimum_vt_amount = batch.add(puffer_protocol_contract.get_minimum_vt_amount());
+    let vt_wallet_balance = batch.add(validator_ticket_contract.balance_of(node));
+    let pufeth_balance = batch.add(puffer_vault_contract.balance_of(node));
+    let results = batch_reader.run(batch).await?;
+    let node_info = results.get(node_info)?;
+    let vt_penalty = results.get(vt_penalty)?;
+    let minimum_vt_amount = results.get(minimum_vt_amount)?;
+    let vt_wallet_balance = results.get(vt_wallet_balance)?;
+    let pufeth_balance = results.get(pufeth_balance)?;
+
+    let pufeth_balance_in_eth = puffer_vault_contract
+        .convert_to_assets(pufeth_balance)
+        .await
+        .map_err(contract_call_error("pufETH exchange rate"))?;
+
+    let vt_balance = U256::from(node_info.vt_balance);
+    let days_remaining = match node_info.active_validator_count {
+        0 => None,
+        count => Some(vt_to_days(vt_balance) / count as f64),
+    };
+
+    let status = NodeStatus {
+        node,
+        active_validator_count: node_info.active_validator_count,
+        pending_validator_count: node_info.pending_validator_count,
+        vt_balance: vt_balance.to_string(),
+        vt_wallet_balance: vt_wallet_balance.to_string(),
+        vt_penalty: vt_penalty.to_string(),
+        minimum_vt_amount: minimum_vt_amount.to_string(),
+        pufeth_balance: pufeth_balance.to_string(),
+        pufeth_balance_in_eth: pufeth_balance_in_eth.to_string(),
+        days_remaining,
+    };
+
+    if json {
+        let status_json = serde_json::to_string_pretty(&status).map_err(|err| {
+            let error_msg = format!("Failed to serialize node status: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{status_json}");
+        return Ok(0);
+    }
+
+    println!("Node: {node:?}");
+    println!("Active validators: {}", status.active_validator_count);
+    println!("Pending validators: {}", status.pending_validator_count);
+    println!("Deposited VT: {}", format_vt(vt_balance));
+    println!("Wallet VT: {}", format_vt(vt_wallet_balance));
+    println!("VT penalty: {}", format_vt(vt_penalty));
+    println!("Minimum VT amount: {}", format_vt(minimum_vt_amount));
+    println!("pufETH: {pufeth_balance} wei (~{pufeth_balance_in_eth} wei ETH)");
+    match days_remaining {
+        Some(days) => println!("Days of tickets left: {days:.2}"),
+        None => println!("Days of tickets left: n/a (no active validators)"),
+    }
+    Ok(0)
+}
@@ -0,0 +1,39 @@
+pub mod stats;
+
+use clap::Subcommand;
+
+use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ProtocolCommand {
+    #[command(about = "Show oracle, validator ticket and vault stats")]
+    Stats {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "json")]
+        json: bool,
+    },
+}
+
+impl ProtocolCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Stats {
+                rpc_url,
+                puffer_protocol_address,
+                json,
+            } => {
+                let rpc_url = resolve::rpc_url(rpc_url, profile).await?;
+                let puffer_protocol_address =
+                    resolve::puffer_protocol_address(puffer_protocol_address, profile)?;
+                stats::protocol_stats(&rpc_url, &puffer_protocol_address, json).await?;
+            }
+        }
+        Ok(0)
+    }
+}
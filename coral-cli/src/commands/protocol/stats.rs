@@ -0,0 +1,163 @@
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::multicall::{BatchReader, CallBatch};
+
+use crate::commands::contracts::puffer_contracts;
+use crate::{PufferOracle, PufferVault, ValidatorTicket};
+
+/// Snapshot of the protocol at `block_number`.
+/// Amounts are in wei and fee rates in basis points, as decimal strings.
+#[derive(Clone, Debug, Serialize)]
+pub struct ProtocolStats {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub locked_eth_amount: String,
+    pub number_of_active_validators: String,
+    pub total_number_of_validators: String,
+    pub is_over_burst_threshold: bool,
+    pub validator_ticket_price: String,
+    pub vt_protocol_fee_rate: String,
+    pub vt_guardians_fee_rate: String,
+    pub vault_total_assets: String,
+    pub vault_total_supply: String,
+}
+
+pub async fn protocol_stats(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::generate_random_wallet();
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let contracts = puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address).await?;
+
+    // Read every value at the same block so the snapshot is consistent
+    let block = utils::ethereum::get_block(&provider, BlockNumber::Latest).await?;
+    let block_number = block.number.unwrap_or_default().as_u64();
+    let block_id = BlockId::from(block_number);
+
+    let puffer_oracle_contract: PufferOracle<_> =
+        PufferOracle::new(contracts.puffer_oracle, client.clone());
+    let validator_ticket_contract: ValidatorTicket<_> =
+        ValidatorTicket::new(contracts.validator_ticket, client.clone());
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    let batch_reader = BatchReader::new(client.clone()).await?;
+    let mut batch = CallBatch::new();
+    let locked_eth_amount = batch.add(
+        puffer_oracle_contract
+            .get_locked_eth_amount()
+            .block(block_id),
+    );
+    let number_of_active_validators = batch.add(
+        puffer_oracle_contract
+            .get_number_of_active_validators()
+            .block(block_id),
+    );
+    let total_number_of_validators = batch.add(
+        puffer_oracle_contract
+            .get_total_number_of_validators()
+            .block(block_id),
+    );
+    let is_over_burst_threshold = batch.add(
+        puffer_oracle_contract
+            .is_over_burst_threshold()
+            .block(block_id),
+    );
+    let validator_ticket_price = batch.add(
+        puffer_oracle_contract
+            .get_validator_ticket_price()
+            .block(block_id),
+    );
+    let vt_protocol_fee_rate = batch.add(
+        validator_ticket_contract
+            .get_protocol_fee_rate()
+            .block(block_id),
+    );
+    let vt_guardians_fee_rate = batch.add(
+        validator_ticket_contract
+            .get_guardians_fee_rate()
+            .block(block_id),
+    );
+    let vault_total_assets = batch.add(puffer_vault_contract.total_assets().block(block_id));
+    let vault_total_supply = batch.add(puffer_vault_contract.total_supply().block(block_id));
+
+    let results = batch_reader.run(batch).await?;
+    let locked_eth_amount = results.get(locked_eth_amount)?;
+    let number_of_active_validators = results.get(number_of_active_validators)?;
+    let total_number_of_validators = results.get(total_number_of_validators)?;
+    let is_over_burst_threshold = results.get(is_over_burst_threshold)?;
+    let validator_ticket_price = results.get(validator_ticket_price)?;
+    let vt_protocol_fee_rate = results.get(vt_protocol_fee_rate)?;
+    let vt_guardians_fee_rate = results.get(vt_guardians_fee_rate)?;
+    let vault_total_assets = results.get(vault_total_assets)?;
+    let vault_total_supply = results.get(vault_total_supply)?;
+
+    let stats = ProtocolStats {
+        chain_id: chain_id.as_u64(),
+        block_number,
+        timestamp: block.timestamp.as_u64(),
+        locked_eth_amount: locked_eth_amount.to_string(),
+        number_of_active_validators: number_of_active_validators.to_string(),
+        total_number_of_validators: total_number_of_validators.to_string(),
+        is_over_burst_threshold,
+        validator_ticket_price: validator_ticket_price.to_string(),
+        vt_protocol_fee_rate: vt_protocol_fee_rate.to_string(),
+        vt_guardians_fee_rate: vt_guardians_fee_rate.to_string(),
+        vault_total_assets: vault_total_assets.to_string(),
+        vault_total_supply: vault_total_supply.to_string(),
+    };
+
+    if json {
+        let stats_json = serde_json::to_string(&stats).map_err(|err| {
+            let error_msg = format!("Failed to serialize protocol stats: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{stats_json}");
+        return Ok(0);
+    }
+
+    println!("Chain: {} at block {block_number}", stats.chain_id);
+    println!("Locked ETH: {} ETH", format_ether(locked_eth_amount));
+    println!(
+        "Validators: {number_of_active_validators} active of {total_number_of_validators} total"
+    );
+    println!("Over burst threshold: {is_over_burst_threshold}");
+    println!(
+        "Validator ticket price: {validator_ticket_price} wei ({} ETH)",
+        format_ether(validator_ticket_price)
+    );
+    println!(
+        "VT protocol fee rate: {vt_protocol_fee_rate} bps ({:.2}%)",
+        basis_points_to_percent(vt_protocol_fee_rate)
+    );
+    println!(
+        "VT guardians fee rate: {vt_guardians_fee_rate} bps ({:.2}%)",
+        basis_points_to_percent(vt_guardians_fee_rate)
+    );
+    println!(
+        "Vault total assets: {} ETH",
+        format_ether(vault_total_assets)
+    );
+    println!(
+        "Vault total supply: {} pufETH",
+        format_ether(vault_total_supply)
+    );
+    Ok(0)
+}
+
+fn basis_points_to_percent(rate: U256) -> f64 {
+    rate.low_u128() as f64 / 100.0
+}
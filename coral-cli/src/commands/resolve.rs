@@ -0,0 +1,141 @@
+#[cfg(feature = "dev")]
+use std::time::Duration;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::profile::Profile;
+#[cfg(feature = "dev")]
+use coral_lib::utils::fallback_client::RpcConfig;
+
+/// Value of a flag, falling back to the selected profile
+fn resolve(
+    flag: Option<String>,
+    profile: Option<&Profile>,
+    from_profile: impl FnOnce(&Profile) -> Option<String>,
+    arg: &str,
+) -> AppResult<String> {
+    flag.or_else(|| profile.and_then(from_profile))
+        .ok_or_else(|| {
+            let error_msg = format!("Missing --{arg}, pass it or set it in the profile");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })
+}
+
+pub fn enclave_url(flag: Option<String>, profile: Option<&Profile>) -> Option<String> {
+    flag.or_else(|| profile.and_then(|profile| profile.enclave_url.clone()))
+}
+
+pub fn required_enclave_url(flag: Option<String>, profile: Option<&Profile>) -> AppResult<String> {
+    resolve(
+        flag,
+        profile,
+        |profile| profile.enclave_url.clone(),
+        "enclave-url",
+    )
+}
+
+pub fn fork_current_version(flag: Option<String>, profile: Option<&Profile>) -> AppResult<String> {
+    resolve(
+        flag,
+        profile,
+        |profile| {
+            profile
+                .fork
+                .as_ref()
+                .map(|fork| fork.current_version.clone())
+        },
+        "fork-current-version",
+    )
+}
+
+pub fn fork_previous_version(flag: Option<String>, profile: Option<&Profile>) -> AppResult<String> {
+    resolve(
+        flag,
+        profile,
+        |profile| {
+            profile
+                .fork
+                .as_ref()
+                .map(|fork| fork.previous_version.clone())
+        },
+        "fork-previous-version",
+    )
+}
+
+pub fn genesis_validators_root(
+    flag: Option<String>,
+    profile: Option<&Profile>,
+) -> AppResult<String> {
+    resolve(
+        flag,
+        profile,
+        |profile| {
+            profile
+                .fork
+                .as_ref()
+                .map(|fork| fork.genesis_validators_root.clone())
+        },
+        "genesis-validators-root",
+    )
+}
+
+/// RPC timeout and retries from the flags, else the profile, else the environment
+#[cfg(feature = "dev")]
+pub fn rpc_config(
+    timeout: Option<u64>,
+    max_retries: Option<u32>,
+    profile: Option<&Profile>,
+) -> RpcConfig {
+    let mut config = RpcConfig::from_env();
+    if let Some(timeout) = timeout.or_else(|| profile.and_then(|profile| profile.rpc_timeout)) {
+        config.timeout = Duration::from_secs(timeout);
+    }
+    if let Some(max_retries) =
+        max_retries.or_else(|| profile.and_then(|profile| profile.rpc_max_retries))
+    {
+        config.max_retries = max_retries;
+    }
+    config
+}
+
+/// RPC URL from the flag or the profile.
+/// With a profile, the RPC must be on the profile's chain.
+#[cfg(feature = "dev")]
+pub async fn rpc_url(flag: Option<String>, profile: Option<&Profile>) -> AppResult<String> {
+    let rpc_url = resolve(flag, profile, Profile::rpc_url, "rpc-url")?;
+    if let Some(profile) = profile {
+        let provider = coral_lib::utils::ethereum::get_provider(&rpc_url).await?;
+        let chain_id = coral_lib::utils::ethereum::get_chain_id(&provider).await?;
+        coral_lib::profile::check_chain_id(profile, chain_id.as_u64())?;
+    }
+    Ok(rpc_url)
+}
+
+#[cfg(feature = "dev")]
+pub fn puffer_protocol_address(
+    flag: Option<String>,
+    profile: Option<&Profile>,
+) -> AppResult<String> {
+    resolve(
+        flag,
+        profile,
+        |profile| profile.contracts.puffer_protocol.clone(),
+        "puffer-protocol-address",
+    )
+}
+
+/// ValidatorTicket address from the flag or the profile.
+/// `None` if it is to be looked up from PufferProtocol.
+#[cfg(feature = "dev")]
+pub fn validator_ticket_address(
+    flag: Option<String>,
+    puffer_protocol_flag: Option<&str>,
+    profile: Option<&Profile>,
+) -> Option<String> {
+    // an explicit --puffer-protocol-address takes precedence over the profile
+    match puffer_protocol_flag {
+        Some(_) => flag,
+        None => {
+            flag.or_else(|| profile.and_then(|profile| profile.contracts.validator_ticket.clone()))
+        }
+    }
+}
@@ -0,0 +1,267 @@
+pub mod replace;
+
+use clap::{Args, Subcommand};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::profile::Profile;
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+use ethers::prelude::Middleware;
+use ethers::types::{transaction::eip2718::TypedTransaction, TransactionReceipt, U256};
+use ethers::utils::{hex, parse_units};
+
+use crate::commands::resolve;
+
+/// Fee caps shared by every command that sends or replaces a transaction
+#[derive(Clone, Debug, Args)]
+pub struct FeeArgs {
+    #[arg(long = "max-fee-per-gas", help = "Max fee per gas in gwei")]
+    pub max_fee_per_gas: Option<String>,
+    #[arg(
+        long = "max-priority-fee-per-gas",
+        help = "Max priority fee per gas in gwei"
+    )]
+    pub max_priority_fee_per_gas: Option<String>,
+}
+
+impl FeeArgs {
+    /// Options with the fee caps set, the rest left to the node's estimates
+    pub fn to_options(&self) -> AppResult<TransactionOptions> {
+        let options = TransactionOptions {
+            max_fee_per_gas: self
+                .max_fee_per_gas
+                .as_deref()
+                .map(parse_gwei)
+                .transpose()?,
+            max_priority_fee_per_gas: self
+                .max_priority_fee_per_gas
+                .as_deref()
+                .map(parse_gwei)
+                .transpose()?,
+            ..Default::default()
+        };
+        if let (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) =
+            (options.max_fee_per_gas, options.max_priority_fee_per_gas)
+        {
+            check_priority_fee(max_fee_per_gas, max_priority_fee_per_gas)?;
+        }
+        Ok(options)
+    }
+}
+
+/// Nodes reject transactions whose priority fee exceeds their max fee
+pub fn check_priority_fee(max_fee_per_gas: U256, max_priority_fee_per_gas: U256) -> AppResult<()> {
+    if max_priority_fee_per_gas > max_fee_per_gas {
+        let error_msg = format!(
+            "Max priority fee per gas ({max_priority_fee_per_gas} wei) exceeds max fee per gas ({max_fee_per_gas} wei)"
+        );
+        return Err(AppError::new(AppErrorKind::TransactionError, error_msg));
+    }
+    Ok(())
+}
+
+/// Transaction options shared by every command that sends a transaction
+#[derive(Clone, Debug, Args)]
+pub struct TxArgs {
+    #[command(flatten)]
+    pub fees: FeeArgs,
+    #[arg(long = "gas-limit")]
+    pub gas_limit: Option<u64>,
+    #[arg(
+        long = "gas-multiplier",
+        help = "Multiplier applied to the estimated gas"
+    )]
+    pub gas_multiplier: Option<f64>,
+    #[arg(long = "nonce")]
+    pub nonce: Option<u64>,
+    #[arg(long = "confirmations", default_value_t = 1)]
+    pub confirmations: usize,
+}
+
+impl TxArgs {
+    pub fn to_options(&self) -> AppResult<TransactionOptions> {
+        if let Some(multiplier) = self.gas_multiplier {
+            if !(multiplier.is_finite() && multiplier > 0.0) {
+                return Err(AppError::new(
+                    AppErrorKind::ParseError,
+                    format!("Invalid gas multiplier: '{multiplier}'"),
+                ));
+            }
+        }
+
+        let options = TransactionOptions {
+            gas_limit: self.gas_limit.map(U256::from),
+            gas_multiplier: self.gas_multiplier,
+            nonce: self.nonce.map(U256::from),
+            confirmations: self.confirmations,
+            ..self.fees.to_options()?
+        };
+        Ok(options)
+    }
+}
+
+pub fn parse_gwei(value: &str) -> AppResult<U256> {
+    let wei = parse_units(value, "gwei").map_err(|err| {
+        AppError::new(
+            AppErrorKind::ParseError,
+            format!("Invalid gwei amount '{value}': {err}"),
+        )
+    })?;
+    Ok(wei.into())
+}
+
+/// Simulate and send the transaction, then wait for the configured number of confirmations
+pub async fn send_and_wait<M: Middleware>(
+    client: &M,
+    tx: TypedTransaction,
+    tx_options: &TransactionOptions,
+) -> AppResult<TransactionReceipt> {
+    let tx_hash =
+        utils::transaction::send_transaction(client, tx, &crate::contract_abis(), tx_options)
+            .await?;
+    println!("Tx Hash: '{}'", hex::encode(tx_hash));
+
+    println!(
+        "Waiting for {} confirmation(s)...",
+        tx_options.confirmations
+    );
+    let receipt =
+        utils::transaction::wait_for_transaction(client, tx_hash, tx_options.confirmations).await?;
+    Ok(receipt)
+}
+
+/// Send `approve_tx` when `allowance` doesn't cover `amount`.
+/// Returns the options of the transaction spending the allowance:
+/// with an explicit nonce, it takes the one after the approval.
+pub async fn approve_if_needed<M: Middleware>(
+    client: &M,
+    approve_tx: TypedTransaction,
+    allowance: U256,
+    amount: U256,
+    description: &str,
+    tx_options: &TransactionOptions,
+) -> AppResult<TransactionOptions> {
+    let mut spend_tx_options = tx_options.clone();
+    if allowance < amount {
+        println!("Approving {description}...");
+        send_and_wait(client, approve_tx, tx_options).await?;
+        spend_tx_options.nonce = tx_options.nonce.map(|nonce| nonce + U256::one());
+    }
+    Ok(spend_tx_options)
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum TxCommand {
+    #[command(about = "Resend a pending transaction with higher fees")]
+    SpeedUp {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(
+            long = "bump-percent",
+            default_value_t = replace::MIN_BUMP_PERCENT,
+            value_parser = clap::value_parser!(u64).range(replace::MIN_BUMP_PERCENT..)
+        )]
+        bump_percent: u64,
+        #[command(flatten)]
+        fees: FeeArgs,
+        #[arg(long = "confirmations", default_value_t = 1)]
+        confirmations: usize,
+        tx_hash: String,
+    },
+    #[command(about = "Replace a pending transaction with an empty transfer to self")]
+    Cancel {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(
+            long = "bump-percent",
+            default_value_t = replace::MIN_BUMP_PERCENT,
+            value_parser = clap::value_parser!(u64).range(replace::MIN_BUMP_PERCENT..)
+        )]
+        bump_percent: u64,
+        #[command(flatten)]
+        fees: FeeArgs,
+        #[arg(long = "confirmations", default_value_t = 1)]
+        confirmations: usize,
+        tx_hash: String,
+    },
+}
+
+impl TxCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::SpeedUp {
+                private_key,
+                rpc_url,
+                bump_percent,
+                fees,
+                confirmations,
+                tx_hash,
+            } => {
+                let data = replace::ReplaceTxInput {
+                    private_key,
+                    rpc_url: resolve::rpc_url(rpc_url, profile).await?,
+                    tx_hash,
+                    bump_percent,
+                    fees,
+                    confirmations,
+                    cancel: false,
+                };
+                replace::replace_transaction(data).await?;
+            }
+            Self::Cancel {
+                private_key,
+                rpc_url,
+                bump_percent,
+                fees,
+                confirmations,
+                tx_hash,
+            } => {
+                let data = replace::ReplaceTxInput {
+                    private_key,
+                    rpc_url: resolve::rpc_url(rpc_url, profile).await?,
+                    tx_hash,
+                    bump_percent,
+                    fees,
+                    confirmations,
+                    cancel: true,
+                };
+                replace::replace_transaction(data).await?;
+            }
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx_args(gas_multiplier: f64) -> TxArgs {
+        TxArgs {
+            fees: FeeArgs {
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+            },
+            gas_limit: None,
+            gas_multiplier: Some(gas_multiplier),
+            nonce: None,
+            confirmations: 1,
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_gas_multiplier() {
+        for multiplier in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(
+                tx_args(multiplier).to_options().is_err(),
+                "accepted gas multiplier {multiplier}"
+            );
+        }
+        let options = tx_args(1.2).to_options().unwrap();
+        assert_eq!(options.gas_multiplier, Some(1.2));
+    }
+}
@@ -0,0 +1,135 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+
+use super::{check_priority_fee, send_and_wait, FeeArgs};
+
+#[derive(Clone, Debug)]
+pub struct ReplaceTxInput {
+    pub private_key: String,
+    pub rpc_url: String,
+    pub tx_hash: String,
+    pub bump_percent: u64,
+    pub fees: FeeArgs,
+    pub confirmations: usize,
+    pub cancel: bool,
+}
+
+/// Fee increase nodes require to replace a pending transaction, geth's default price bump
+pub const MIN_BUMP_PERCENT: u64 = 10;
+
+fn bump(value: U256, bump_percent: u64) -> U256 {
+    // round up so that a 10% bump of a small fee is still accepted by the node
+    let bumped = value.saturating_mul(U256::from(100 + bump_percent));
+    (bumped + U256::from(99)) / U256::from(100)
+}
+
+/// Nodes reject a replacement unless each fee is at least `MIN_BUMP_PERCENT` above the original
+fn check_replacement_fee(what: &str, fee: U256, original_fee: U256) -> AppResult<()> {
+    let min_fee = bump(original_fee, MIN_BUMP_PERCENT);
+    if fee < min_fee {
+        let error_msg = format!(
+            "A {what} of {fee} wei is too low to replace the transaction, \
+             it must be at least {min_fee} wei ({MIN_BUMP_PERCENT}% above {original_fee} wei)"
+        );
+        return Err(AppError::new(AppErrorKind::TransactionError, error_msg));
+    }
+    Ok(())
+}
+
+/// Resend a pending transaction with the same nonce and higher fees.
+/// When `cancel` is set the replacement is an empty transfer to self
+pub async fn replace_transaction(input: ReplaceTxInput) -> AppResult<i32> {
+    let tx_hash: H256 = input.tx_hash.parse().map_err(|_| {
+        AppError::new(
+            AppErrorKind::DecodeError,
+            format!("Invalid transaction hash: '{}'", input.tx_hash),
+        )
+    })?;
+
+    let provider = utils::ethereum::get_provider(&input.rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(&input.private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let original_tx = utils::transaction::get_pending_transaction(&provider, tx_hash).await?;
+    if original_tx.from != wallet.address() {
+        let error_msg = format!(
+            "Transaction was sent by {:?}, not by {:?}",
+            original_tx.from,
+            wallet.address()
+        );
+        return Err(AppError::new(AppErrorKind::TransactionError, error_msg));
+    }
+    let fees = input.fees.to_options()?;
+
+    let (network_max_fee, network_priority_fee) =
+        client.estimate_eip1559_fees(None).await.map_err(|err| {
+            let error_msg = format!("Failed to estimate fees: {err}");
+            AppError::new(AppErrorKind::ContractCallError, error_msg)
+        })?;
+
+    let original_max_fee = original_tx
+        .max_fee_per_gas
+        .or(original_tx.gas_price)
+        .unwrap_or_default();
+    let original_priority_fee = original_tx
+        .max_priority_fee_per_gas
+        .or(original_tx.gas_price)
+        .unwrap_or_default();
+
+    let max_fee_per_gas = match fees.max_fee_per_gas {
+        Some(fee) => {
+            check_replacement_fee("max fee per gas", fee, original_max_fee)?;
+            fee
+        }
+        None => bump(original_max_fee, input.bump_percent).max(network_max_fee),
+    };
+    let max_priority_fee_per_gas = match fees.max_priority_fee_per_gas {
+        Some(fee) => {
+            check_replacement_fee("max priority fee per gas", fee, original_priority_fee)?;
+            fee
+        }
+        None => bump(original_priority_fee, input.bump_percent).max(network_priority_fee),
+    };
+    check_priority_fee(max_fee_per_gas, max_priority_fee_per_gas)?;
+
+    let replacement = if input.cancel {
+        Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(wallet.address())
+            .value(U256::zero())
+            .gas(21_000)
+    } else {
+        let mut replacement = Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .value(original_tx.value)
+            .data(original_tx.input.clone())
+            .gas(original_tx.gas);
+        if let Some(to) = original_tx.to {
+            replacement = replacement.to(to);
+        }
+        replacement
+    };
+    let replacement: TypedTransaction = replacement
+        .nonce(original_tx.nonce)
+        .chain_id(chain_id.as_u64())
+        .max_fee_per_gas(max_fee_per_gas)
+        .max_priority_fee_per_gas(max_priority_fee_per_gas)
+        .into();
+
+    println!(
+        "Replacing transaction with nonce {} (max fee: {} wei, priority fee: {} wei)...",
+        original_tx.nonce, max_fee_per_gas, max_priority_fee_per_gas
+    );
+
+    let tx_options = utils::transaction::TransactionOptions {
+        confirmations: input.confirmations,
+        ..Default::default()
+    };
+    send_and_wait(client.as_ref(), replacement, &tx_options).await?;
+    println!("Transaction confirmed");
+    Ok(0)
+}
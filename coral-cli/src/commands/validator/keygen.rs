@@ -4,7 +4,6 @@ use axum::http::StatusCode;
 
 use colored::Colorize;
 
-use coral_lib::utils::parse::parse_module_name;
 use ecies::PublicKey as EthPublicKey;
 
 use hex::ToHex;
@@ -18,23 +17,15 @@ use coral_lib::error::{AppError, AppErrorKind, AppResult};
 use coral_lib::error::{ServerErrorCode, ServerErrorResponse};
 use coral_lib::strip_0x_prefix;
 use coral_lib::structs::eth_types::WithdrawalCredentials;
+use coral_lib::structs::ModuleName;
 
 use crate::APP_VERSION;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ForkVersionInfo {
-    pub current_version: String,
-    pub previous_version: String,
-    pub genesis_version: String,
-    pub genesis_validators_root: String,
-    pub epoch: u64,
-}
-
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BlsKeygenInput {
     pub guardian_pubkeys: Vec<String>,
     pub guardian_threshold: u64,
-    pub module_name: String,
+    pub module_name: ModuleName,
     pub withdrawal_credentials: String,
     pub fork_version: String,
     pub output_file: String,
@@ -47,7 +38,7 @@ pub struct BlsKeygenOutput {
     pub version: String,
     pub guardian_threshold: u64,
     pub guardian_pubkeys: Vec<String>,
-    pub module_name: String,
+    pub module_name: ModuleName,
     pub withdrawal_credentials: String,
     pub fork_version: String,
     pub signature: String,
@@ -90,10 +81,9 @@ pub async fn keygen_from_cmd(data: KeygenCmdInput) -> AppResult<i32> {
     let password = match password_file {
         None => None,
         Some(path) => {
-            let password = std::fs::read_to_string(path).map_err(|err| {
+            let password = std::fs::read_to_string(path).inspect_err(|_| {
                 let error_msg = "Failed to read password file";
                 eprintln!("{}", error_msg.red());
-                err
             })?;
             Some(password.trim().to_string())
         }
@@ -102,7 +92,7 @@ pub async fn keygen_from_cmd(data: KeygenCmdInput) -> AppResult<i32> {
     let input_data = BlsKeygenInput {
         guardian_pubkeys,
         guardian_threshold,
-        module_name,
+        module_name: ModuleName::parse(&module_name)?,
         withdrawal_credentials,
         fork_version,
         enclave_url,
@@ -114,8 +104,6 @@ pub async fn keygen_from_cmd(data: KeygenCmdInput) -> AppResult<i32> {
 }
 
 pub async fn register_validator(input_data: &BlsKeygenInput) -> AppResult<i32> {
-    let module_name = parse_module_name(&input_data.module_name)?;
-
     let mut guardian_pubkeys = Vec::with_capacity(input_data.guardian_pubkeys.len());
     for key in input_data.guardian_pubkeys.iter() {
         let key = strip_0x_prefix(key);
@@ -252,7 +240,7 @@ pub async fn register_validator(input_data: &BlsKeygenInput) -> AppResult<i32> {
         version: APP_VERSION.to_string(),
         guardian_threshold: input_data.guardian_threshold,
         guardian_pubkeys: bls_keygen_payload.guardian_eth_pub_keys,
-        module_name: module_name.encode_hex(),
+        module_name: input_data.module_name,
         withdrawal_credentials: hex::encode(withdrawal_credentials),
         fork_version: genesis_fork_version.encode_hex(),
 
@@ -274,10 +262,13 @@ pub async fn register_validator(input_data: &BlsKeygenInput) -> AppResult<i32> {
         file.write_all(json_string_pretty.as_bytes())?;
     }
 
-    std::fs::rename(
-        format!("etc/keys/bls_keys/{}", registraton_payload.bls_pub_key),
-        format!("etc/keys/bls_keys/{}.json", registraton_payload.bls_pub_key),
-    )?;
+    // an enclave keeps the keystore, without one it was saved locally
+    if !enclave_enabled {
+        std::fs::rename(
+            format!("etc/keys/bls_keys/{}", registraton_payload.bls_pub_key),
+            format!("etc/keys/bls_keys/{}.json", registraton_payload.bls_pub_key),
+        )?;
+    }
 
     Ok(0)
 }
@@ -2,10 +2,18 @@ pub mod keygen;
 pub mod list_keys;
 pub mod sign_vem;
 
+#[cfg(feature = "dev")]
+pub mod preflight;
 #[cfg(feature = "dev")]
 pub mod register_calldata;
 #[cfg(feature = "dev")]
 pub mod register_key;
+#[cfg(feature = "dev")]
+pub mod register_keys;
+#[cfg(feature = "dev")]
+pub mod registration_receipt;
+#[cfg(feature = "dev")]
+pub mod validator_status;
 
 #[cfg(feature = "dev")]
 pub mod withdrawal_credentials;
@@ -16,6 +24,12 @@ use std::path::PathBuf;
 use clap::Subcommand;
 
 use coral_lib::error::AppResult;
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+
+#[cfg(feature = "dev")]
+use crate::commands::tx::TxArgs;
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum ValidatorCommand {
@@ -39,7 +53,7 @@ pub enum ValidatorCommand {
         #[arg(long = "withdrawal-credentials")]
         withdrawal_credentials: String,
         #[arg(long = "fork-version")]
-        fork_version: String,
+        fork_version: Option<String>,
         #[arg(long = "enclave-url")]
         enclave_url: Option<String>,
         #[arg(long = "password-file")]
@@ -53,29 +67,45 @@ pub enum ValidatorCommand {
         #[arg(long = "private-key")]
         private_key: String,
         #[arg(long = "rpc-url")]
-        rpc_url: String,
-        #[arg(long = "puffer-oracle-address")]
-        puffer_oracle_address: String,
+        rpc_url: Option<String>,
         #[arg(long = "puffer-protocol-address")]
-        puffer_protocol_address: String,
-        #[arg(long = "validator-ticket-address")]
-        validator_ticket_address: String,
+        puffer_protocol_address: Option<String>,
         #[arg(long = "module-name")]
         module_name: String,
         #[arg(long = "number-of-days")]
         number_of_days: u64,
         #[arg(long = "input-file")]
         input_file: PathBuf,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[cfg(feature = "dev")]
+    #[command(about = "Register every keygen output in a directory (for testing only)")]
+    RegisterKeys {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "module-name")]
+        module_name: String,
+        #[arg(long = "number-of-days")]
+        number_of_days: u64,
+        #[arg(long = "input-dir")]
+        input_dir: PathBuf,
+        #[arg(long = "output-file")]
+        output_file: PathBuf,
+        #[command(flatten)]
+        tx_args: TxArgs,
     },
     #[cfg(feature = "dev")]
     #[command(about = "Generate calldata for registering a validator (for testing only)")]
     GenerateRegisterCalldata {
         #[arg(long = "rpc-url")]
-        rpc_url: String,
+        rpc_url: Option<String>,
         #[arg(long = "puffer-protocol-address")]
-        puffer_protocol_address: String,
-        #[arg(long = "validator-ticket-address")]
-        validator_ticket_address: String,
+        puffer_protocol_address: Option<String>,
         #[arg(long = "module-name")]
         module_name: String,
         #[arg(long = "input-file")]
@@ -87,15 +117,15 @@ pub enum ValidatorCommand {
         #[arg(long = "beacon-index")]
         beacon_index: u64,
         #[arg(long = "enclave-url")]
-        enclave_url: String,
+        enclave_url: Option<String>,
         #[arg(long = "fork-previous-version")]
-        fork_previous_version: String,
+        fork_previous_version: Option<String>,
         #[arg(long = "fork-current-version")]
-        fork_current_version: String,
+        fork_current_version: Option<String>,
         #[arg(long = "epoch")]
         epoch: u64,
         #[arg(long = "genesis-validators-root")]
-        genesis_validators_root: String,
+        genesis_validators_root: Option<String>,
         #[arg(long = "output-file")]
         output_file: String,
     },
@@ -103,22 +133,43 @@ pub enum ValidatorCommand {
     #[command(about = "Fetch withdrawal credentials for a given module")]
     WithdrawalCredentials {
         #[arg(long = "rpc-url")]
-        rpc_url: String,
+        rpc_url: Option<String>,
         #[arg(long = "puffer-protocol-address")]
-        puffer_protocol_address: String,
+        puffer_protocol_address: Option<String>,
         #[arg(long = "module-address")]
         module_address: String,
     },
+    #[cfg(feature = "dev")]
+    #[command(about = "Show the status and provisioning queue position of a validator")]
+    Status {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "module-name")]
+        module_name: String,
+        #[arg(
+            long = "index",
+            required_unless_present = "bls_pubkey",
+            conflicts_with = "bls_pubkey"
+        )]
+        index: Option<u64>,
+        #[arg(long = "bls-public-key")]
+        bls_pubkey: Option<String>,
+        #[arg(long = "json")]
+        json: bool,
+    },
 }
 
 impl ValidatorCommand {
-    pub async fn execute(self) -> AppResult<i32> {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
         match self {
             Self::ListKeys {
                 disable_enclave,
                 keystore_path,
                 enclave_url,
             } => {
+                let enclave_url = resolve::enclave_url(enclave_url, profile);
                 list_keys::list_keys(disable_enclave, keystore_path, enclave_url).await?;
             }
             Self::Keygen {
@@ -136,8 +187,8 @@ impl ValidatorCommand {
                     guardian_threshold,
                     module_name,
                     withdrawal_credentials,
-                    fork_version,
-                    enclave_url,
+                    fork_version: resolve::fork_current_version(fork_version, profile)?,
+                    enclave_url: resolve::enclave_url(enclave_url, profile),
                     password_file,
                     output_file,
                 };
@@ -150,8 +201,8 @@ impl ValidatorCommand {
                 module_address,
             } => {
                 withdrawal_credentials::get_withdrawal_credentials(
-                    &rpc_url,
-                    &puffer_protocol_address,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
                     &module_address,
                 )
                 .await?;
@@ -167,13 +218,13 @@ impl ValidatorCommand {
                 output_file,
             } => {
                 sign_vem::sign_vem_from_cmd(
-                    enclave_url,
+                    resolve::required_enclave_url(enclave_url, profile)?,
                     bls_pubkey,
                     beacon_index,
-                    fork_current_version,
-                    fork_previous_version,
+                    resolve::fork_current_version(fork_current_version, profile)?,
+                    resolve::fork_previous_version(fork_previous_version, profile)?,
                     epoch,
-                    genesis_validators_root,
+                    resolve::genesis_validators_root(genesis_validators_root, profile)?,
                     output_file,
                 )
                 .await?;
@@ -182,42 +233,80 @@ impl ValidatorCommand {
             Self::RegisterKey {
                 private_key,
                 rpc_url,
-                puffer_oracle_address,
                 puffer_protocol_address,
-                validator_ticket_address,
                 module_name,
                 number_of_days,
                 input_file,
+                tx_args,
             } => {
                 register_key::register_validator_key(
                     &private_key,
-                    &rpc_url,
-                    &puffer_oracle_address,
-                    &puffer_protocol_address,
-                    &validator_ticket_address,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
                     &module_name,
                     number_of_days,
                     input_file.as_path(),
+                    &tx_args.to_options()?,
                 )
                 .await?;
             }
             #[cfg(feature = "dev")]
+            Self::RegisterKeys {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                module_name,
+                number_of_days,
+                input_dir,
+                output_file,
+                tx_args,
+            } => {
+                return register_keys::register_validator_keys(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &module_name,
+                    number_of_days,
+                    input_dir.as_path(),
+                    output_file.as_path(),
+                    &tx_args.to_options()?,
+                )
+                .await;
+            }
+            #[cfg(feature = "dev")]
             Self::GenerateRegisterCalldata {
                 rpc_url,
                 puffer_protocol_address,
-                validator_ticket_address,
                 module_name,
                 input_file,
             } => {
                 register_calldata::generate_register_calldata(
-                    &rpc_url,
-                    &puffer_protocol_address,
-                    &validator_ticket_address,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
                     &module_name,
                     input_file.as_path(),
                 )
                 .await?;
             }
+            #[cfg(feature = "dev")]
+            Self::Status {
+                rpc_url,
+                puffer_protocol_address,
+                module_name,
+                index,
+                bls_pubkey,
+                json,
+            } => {
+                validator_status::validator_status(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &module_name,
+                    index,
+                    bls_pubkey.as_deref(),
+                    json,
+                )
+                .await?;
+            }
         }
         Ok(0)
     }
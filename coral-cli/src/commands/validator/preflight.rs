@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use axum::http::StatusCode;
+use ethers::prelude::*;
+use ethers::utils::hex;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult, ServerErrorCode, ServerErrorResponse};
+use coral_lib::strip_0x_prefix;
+use coral_lib::structs::ModuleName;
+
+use crate::commands::validator::keygen::BlsKeygenOutput;
+use crate::PufferProtocol;
+
+/// PufferProtocol state needed to check a registration before sending it
+#[derive(Clone, Debug)]
+pub struct ModuleRegistrationState {
+    pub module_address: Address,
+    pub withdrawal_credentials: String,
+    pub allowed_limit: u128,
+    pub number_of_registered_validators: u128,
+    pub registered_pubkeys: HashSet<String>,
+}
+
+fn normalize_hex(value: &str) -> String {
+    strip_0x_prefix(value).to_lowercase()
+}
+
+fn preflight_error(error_code: ServerErrorCode, error_msg: String) -> AppError {
+    ServerErrorResponse::new(StatusCode::BAD_REQUEST, error_code, error_msg).into()
+}
+
+impl ModuleRegistrationState {
+    /// Fetch the module state.
+    /// Fails if the module does not exist or the protocol is paused
+    pub async fn fetch<M: Middleware + 'static>(
+        puffer_protocol_contract: &PufferProtocol<M>,
+        module_name: ModuleName,
+    ) -> AppResult<Self> {
+        let module_name_bytes = module_name.to_bytes();
+
+        let module_address = puffer_protocol_contract
+            .get_module_address(module_name_bytes)
+            .await
+            .map_err(|err| {
+                let error_msg = format!("Failed to fetch module address: {err}");
+                AppError::new(AppErrorKind::ContractCallError, error_msg)
+            })?;
+        if module_address.is_zero() {
+            let error_msg = format!("Module '{module_name}' does not exist");
+            return Err(preflight_error(
+                ServerErrorCode::PufferModuleNotFound,
+                error_msg,
+            ));
+        }
+
+        if let Err(err) = puffer_protocol_contract.revert_if_paused().call().await {
+            if err.is_revert() {
+                let error_msg = "PufferProtocol is paused".to_string();
+                return Err(preflight_error(
+                    ServerErrorCode::PufferProtocolPaused,
+                    error_msg,
+                ));
+            }
+            let error_msg = format!("Failed to check if PufferProtocol is paused: {err}");
+            return Err(AppError::new(AppErrorKind::ContractCallError, error_msg));
+        }
+
+        let module_limit = puffer_protocol_contract
+            .get_module_limit_information(module_name_bytes)
+            .await
+            .map_err(|err| {
+                let error_msg = format!("Failed to fetch module limit: {err}");
+                AppError::new(AppErrorKind::ContractCallError, error_msg)
+            })?;
+
+        let withdrawal_credentials = puffer_protocol_contract
+            .get_withdrawal_credentials(module_address)
+            .await
+            .map_err(|err| {
+                let error_msg = format!("Failed to fetch withdrawal credentials: {err}");
+                AppError::new(AppErrorKind::ContractCallError, error_msg)
+            })?;
+
+        let registered_pubkeys = puffer_protocol_contract
+            .get_validators(module_name_bytes)
+            .await
+            .map_err(|err| {
+                let error_msg = format!("Failed to fetch validators: {err}");
+                AppError::new(AppErrorKind::ContractCallError, error_msg)
+            })?
+            .iter()
+            .map(|validator| hex::encode(&validator.pub_key))
+            .collect();
+
+        Ok(Self {
+            module_address,
+            withdrawal_credentials: hex::encode(withdrawal_credentials),
+            allowed_limit: module_limit.allowed_limit,
+            number_of_registered_validators: module_limit.number_of_registered_validators,
+            registered_pubkeys,
+        })
+    }
+
+    pub fn is_registered(&self, bls_pub_key: &str) -> bool {
+        self.registered_pubkeys
+            .contains(&normalize_hex(bls_pub_key))
+    }
+
+    /// Check that all keys can be registered into the module
+    pub fn check_keys(&self, keygen_outputs: &[&BlsKeygenOutput]) -> AppResult {
+        for keygen_data in keygen_outputs.iter() {
+            if self.is_registered(&keygen_data.bls_pub_key) {
+                let error_msg = format!(
+                    "BLS public key '{}' is already registered",
+                    keygen_data.bls_pub_key
+                );
+                return Err(preflight_error(
+                    ServerErrorCode::PufferValidatorAlreadyRegistered,
+                    error_msg,
+                ));
+            }
+
+            if normalize_hex(&keygen_data.withdrawal_credentials) != self.withdrawal_credentials {
+                let error_msg = format!(
+                    "Withdrawal credentials '{}' of '{}' do not match the module's '0x{}'",
+                    keygen_data.withdrawal_credentials,
+                    keygen_data.bls_pub_key,
+                    self.withdrawal_credentials
+                );
+                return Err(preflight_error(
+                    ServerErrorCode::PufferWithdrawalCredentialsMismatch,
+                    error_msg,
+                ));
+            }
+        }
+
+        let available = self
+            .allowed_limit
+            .saturating_sub(self.number_of_registered_validators);
+        if keygen_outputs.len() as u128 > available {
+            let error_msg = format!(
+                "Module has room for {available} more validator(s), {} requested (limit: {})",
+                keygen_outputs.len(),
+                self.allowed_limit
+            );
+            return Err(preflight_error(
+                ServerErrorCode::PufferModuleLimitReached,
+                error_msg,
+            ));
+        }
+        Ok(())
+    }
+}
@@ -8,18 +8,16 @@ use ethers::prelude::{types::Bytes, U256};
 use ethers::types::Address;
 use ethers::utils::hex;
 
+use coral_lib::structs::ModuleName;
 use coral_lib::utils;
 
-use crate::{
-    commands::validator::keygen::BlsKeygenOutput, Permit, ValidatorKeyData, ValidatorTicket,
-};
+use crate::{commands::validator::keygen::BlsKeygenOutput, Permit, ValidatorKeyData};
 
 use crate::PufferProtocol;
 
 pub async fn generate_register_calldata(
     rpc_url: &str,
     puffer_protocol_address: &str,
-    validator_ticket_address: &str,
     module_name: &str,
     input_file: &path::Path,
 ) -> AppResult<i32> {
@@ -33,20 +31,9 @@ pub async fn generate_register_calldata(
         )
     })?;
 
-    let validator_ticket_address_h160: Address =
-        validator_ticket_address.parse().map_err(|_| {
-            AppError::new(
-                AppErrorKind::DecodeError,
-                format!(
-                    "Invalid Validator Ticket address: '{}'",
-                    validator_ticket_address
-                ),
-            )
-        })?;
-
-    let module_name = utils::parse::parse_module_name(module_name)?;
+    let module_name = ModuleName::parse(module_name)?;
 
-    let provider = utils::ethereum::get_provider(rpc_url)?;
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
 
     let wallet = utils::wallet::generate_random_wallet();
 
@@ -104,9 +91,6 @@ pub async fn generate_register_calldata(
         rave_evidence: rave_evidence.into(),
     };
 
-    let _validator_ticket_contract: ValidatorTicket<_> =
-        ValidatorTicket::new(validator_ticket_address_h160, client.clone());
-
     let puf_eth_deposit_permit = Permit {
         deadline: U256::zero(),
         amount: U256::zero(),
@@ -129,7 +113,7 @@ pub async fn generate_register_calldata(
     let calldata = puffer_protocol_contract
         .register_validator_key(
             validator_data,
-            module_name,
+            module_name.to_bytes(),
             puf_eth_deposit_permit,
             vt_deposit_permit,
         )
@@ -4,77 +4,36 @@ use coral_lib::{
     error::{AppError, AppErrorKind, AppResult},
     strip_0x_prefix,
 };
-use ethers::{signers::LocalWallet, types::Address};
+use ethers::types::Address;
 
 use ethers::prelude::*;
-use ethers::utils::hex::{self, ToHex};
+use ethers::utils::hex;
 
+use coral_lib::structs::ModuleName;
 use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
 
-use crate::{
-    commands::validator::keygen::BlsKeygenOutput, Permit, PufferOracle, ValidatorKeyData,
-    ValidatorTicket,
-};
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::tx::send_and_wait;
+use crate::commands::validator::preflight::ModuleRegistrationState;
+use crate::commands::validator::registration_receipt::RegistrationReceipt;
+use crate::{commands::validator::keygen::BlsKeygenOutput, Permit, PufferOracle, ValidatorKeyData};
 
 use crate::PufferProtocol;
 
-#[allow(clippy::too_many_arguments)]
-pub async fn register_validator_key(
-    private_key: &str,
-    rpc_url: &str,
-    puffer_oracle_address: &str,
-    puffer_protocol_address: &str,
-    validator_ticket_address: &str,
-    module_name: &str,
-    number_of_days: u64,
-    input_file: &path::Path,
-) -> AppResult<i32> {
-    let puffer_protocol_address_h160: Address = puffer_protocol_address.parse().map_err(|_| {
-        AppError::new(
-            AppErrorKind::DecodeError,
-            format!(
-                "Invalid Puffer Protocol address: '{}'",
-                puffer_protocol_address
-            ),
-        )
-    })?;
-
-    let puffer_oracle_address_h160: Address = puffer_oracle_address.parse().map_err(|_| {
-        AppError::new(
-            AppErrorKind::DecodeError,
-            format!("Invalid Puffer Oracle address: '{}'", puffer_oracle_address),
-        )
-    })?;
-
-    let validator_ticket_address_h160: Address =
-        validator_ticket_address.parse().map_err(|_| {
-            AppError::new(
-                AppErrorKind::DecodeError,
-                format!(
-                    "Invalid Validator Ticket address: '{}'",
-                    validator_ticket_address
-                ),
-            )
-        })?;
-
-    let module_name = utils::parse::parse_module_name(module_name)?;
-
-    let provider = utils::ethereum::get_provider(rpc_url)?;
-
-    println!("Parsing private key...");
-    let priv_key_bytes = hex::decode(strip_0x_prefix(private_key)).unwrap();
-    let wallet = LocalWallet::from_bytes(&priv_key_bytes).unwrap();
-
-    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
-    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
-
+pub fn read_keygen_output(input_file: &path::Path) -> AppResult<BlsKeygenOutput> {
     let content = std::fs::read_to_string(input_file)?;
-    let keygen_data: BlsKeygenOutput = serde_json::from_str(&content).unwrap();
-
-    println!("Generating calldata...");
-
-    let enclave_enabled = !keygen_data.intel_report.is_empty();
+    let keygen_data: BlsKeygenOutput = serde_json::from_str(&content).map_err(|err| {
+        let error_msg = format!(
+            "Failed to parse keygen output '{}': {err}",
+            input_file.display()
+        );
+        AppError::new(AppErrorKind::JsonDeError, error_msg)
+    })?;
+    Ok(keygen_data)
+}
 
+pub fn validator_key_data(keygen_data: &BlsKeygenOutput) -> AppResult<ValidatorKeyData> {
     let intel_report = keygen_data.intel_report.as_bytes();
     let intel_sig = keygen_data.intel_sig.as_bytes();
     let intel_x509 = keygen_data.intel_x509.as_bytes();
@@ -98,19 +57,26 @@ pub async fn register_validator_key(
         AppError::new(AppErrorKind::DecodeError, error_msg)
     })?;
 
-    let bls_encrypted_priv_key_shares: Vec<Bytes> = keygen_data
-        .bls_enc_priv_key_shares
-        .iter()
-        .map(|keyshare| {
-            let keyshare: &str = strip_0x_prefix(keyshare);
-            hex::decode(keyshare).unwrap().into()
-        })
-        .collect();
+    let mut bls_encrypted_priv_key_shares: Vec<Bytes> =
+        Vec::with_capacity(keygen_data.bls_enc_priv_key_shares.len());
+    for keyshare in keygen_data.bls_enc_priv_key_shares.iter() {
+        let keyshare = hex::decode(strip_0x_prefix(keyshare)).map_err(|err| {
+            let error_msg = format!("Failed to decode BLS key share: {err}");
+            AppError::new(AppErrorKind::DecodeError, error_msg)
+        })?;
+        bls_encrypted_priv_key_shares.push(keyshare.into());
+    }
 
     let deposit_data_root: [u8; 32] = hex::decode(strip_0x_prefix(&keygen_data.deposit_data_root))
-        .unwrap()
+        .map_err(|err| {
+            let error_msg = format!("Failed to decode deposit data root: {err}");
+            AppError::new(AppErrorKind::DecodeError, error_msg)
+        })?
         .try_into()
-        .unwrap();
+        .map_err(|_| {
+            let error_msg = "Invalid deposit data root length".to_string();
+            AppError::new(AppErrorKind::DecodeError, error_msg)
+        })?;
 
     let validator_data = ValidatorKeyData {
         bls_pub_key: bls_pub_key.into(),
@@ -120,28 +86,80 @@ pub async fn register_validator_key(
         bls_pub_key_set: bls_pub_key_set.into(),
         rave_evidence: rave_evidence.into(),
     };
+    Ok(validator_data)
+}
 
-    let _validator_ticket_contract: ValidatorTicket<_> =
-        ValidatorTicket::new(validator_ticket_address_h160, client.clone());
+/// ETH to send with `registerValidatorKey`: the bond plus validator tickets for `number_of_days`
+pub fn registration_value(
+    keygen_data: &BlsKeygenOutput,
+    vt_price: U256,
+    number_of_days: u64,
+) -> U256 {
+    let enclave_enabled = !keygen_data.intel_report.is_empty();
 
-    let puf_eth_deposit_permit = Permit {
-        deadline: U256::zero(),
-        amount: U256::zero(),
-        v: 0,
-        r: [0; 32],
-        s: [0; 32],
-    };
+    let eth_1 = U256::from(1).saturating_mul(U256::exp10(18));
+    let eth_2 = U256::from(2).saturating_mul(U256::exp10(18));
 
-    let vt_deposit_permit = Permit {
+    let total_vt_price = vt_price.saturating_mul(U256::from(number_of_days));
+
+    if enclave_enabled {
+        eth_1.saturating_add(total_vt_price)
+    } else {
+        eth_2.saturating_add(total_vt_price)
+    }
+}
+
+/// Permit used when paying with ETH instead of pufETH / VT
+pub fn empty_permit() -> Permit {
+    Permit {
         deadline: U256::zero(),
         amount: U256::zero(),
         v: 0,
         r: [0; 32],
         s: [0; 32],
-    };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn register_validator_key(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    module_name: &str,
+    number_of_days: u64,
+    input_file: &path::Path,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160: Address = puffer_protocol_address.parse().map_err(|_| {
+        AppError::new(
+            AppErrorKind::DecodeError,
+            format!(
+                "Invalid Puffer Protocol address: '{}'",
+                puffer_protocol_address
+            ),
+        )
+    })?;
+
+    let module_name = ModuleName::parse(module_name)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+
+    println!("Parsing private key...");
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let keygen_data = read_keygen_output(input_file)?;
+
+    println!("Generating calldata...");
 
+    let validator_data = validator_key_data(&keygen_data)?;
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
     let puffer_oracle_contract: PufferOracle<_> =
-        PufferOracle::new(puffer_oracle_address_h160, client.clone());
+        PufferOracle::new(contracts.puffer_oracle, client.clone());
 
     let vt_price: U256 = puffer_oracle_contract
         .get_validator_ticket_price()
@@ -154,48 +172,32 @@ pub async fn register_validator_key(
     let puffer_protocol_contract: PufferProtocol<_> =
         PufferProtocol::new(puffer_protocol_address_h160, client.clone());
 
-    let eth_1 = U256::from(1).saturating_mul(U256::exp10(18));
-    let eth_2 = U256::from(2).saturating_mul(U256::exp10(18));
-
-    let total_vt_price = vt_price.saturating_mul(U256::from(number_of_days));
+    println!("Running pre-flight checks...");
+    let module_state =
+        ModuleRegistrationState::fetch(&puffer_protocol_contract, module_name).await?;
+    println!("Module address: {:?}", module_state.module_address);
+    module_state.check_keys(&[&keygen_data])?;
 
     println!("Registering validator to smart contract...");
+    let value = registration_value(&keygen_data, vt_price, number_of_days);
     let function_call = puffer_protocol_contract
         .register_validator_key(
             validator_data,
-            module_name,
-            puf_eth_deposit_permit,
-            vt_deposit_permit,
+            module_name.to_bytes(),
+            empty_permit(),
+            empty_permit(),
         )
-        .value(if enclave_enabled {
-            eth_1.saturating_add(total_vt_price)
-        } else {
-            eth_2.saturating_add(total_vt_price)
-        });
-
-    let res = function_call.send().await;
-
-    match res {
-        Ok(pending_tx) => {
-            let tx = pending_tx
-                .await
-                .map_err(|err| {
-                    let error_msg = format!("Failed to wait for pending transaction: {err}");
-                    AppError::new(AppErrorKind::ContractCallError, error_msg)
-                })?
-                .ok_or_else(|| {
-                    AppError::new(
-                        AppErrorKind::ContractCallError,
-                        "No transaction receipt".to_string(),
-                    )
-                })?;
-            let tx_hash: String = tx.transaction_hash.encode_hex();
-            println!("Tx Hash: '{tx_hash}'");
-            Ok(0)
-        }
-        Err(err) => {
-            let err = AppError::new(AppErrorKind::ContractCallError, err.to_string());
-            Err(err)
-        }
-    }
+        .value(value);
+
+    let receipt = send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    let registration_receipt =
+        RegistrationReceipt::from_receipt(&receipt, puffer_protocol_address_h160, value)?;
+    let json_string_pretty = serde_json::to_string_pretty(&registration_receipt)?;
+    println!("{json_string_pretty}");
+
+    let receipt_file = RegistrationReceipt::path_for(input_file);
+    registration_receipt.write(&receipt_file)?;
+    println!("Receipt written to '{}'", receipt_file.display());
+    Ok(0)
 }
@@ -0,0 +1,401 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path;
+
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::strip_0x_prefix;
+use coral_lib::structs::ModuleName;
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::validator::keygen::BlsKeygenOutput;
+use crate::commands::validator::preflight::ModuleRegistrationState;
+use crate::commands::validator::register_key::{
+    empty_permit, read_keygen_output, registration_value, validator_key_data,
+};
+use crate::commands::validator::registration_receipt::RegistrationReceipt;
+use crate::{PufferOracle, PufferProtocol};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStatus {
+    AlreadyRegistered,
+    Failed,
+    Sent,
+    Confirmed,
+    Reverted,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegistrationResult {
+    pub input_file: String,
+    pub status: RegistrationStatus,
+    pub tx_hash: Option<String>,
+    pub puffer_module_index: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Results of a batch registration, keyed by BLS public key
+pub type RegistrationManifest = BTreeMap<String, RegistrationResult>;
+
+fn write_manifest(output_file: &path::Path, manifest: &RegistrationManifest) -> AppResult {
+    let json_string_pretty = serde_json::to_string_pretty(manifest)?;
+    let mut file = std::fs::File::create(output_file)?;
+    file.write_all(json_string_pretty.as_bytes())?;
+    Ok(())
+}
+
+/// Results of a previous run, to resume from
+fn read_manifest(output_file: &path::Path) -> AppResult<RegistrationManifest> {
+    if !output_file.exists() {
+        return Ok(RegistrationManifest::new());
+    }
+    let content = std::fs::read_to_string(output_file)?;
+    serde_json::from_str(&content).map_err(|err| {
+        let error_msg = format!(
+            "Failed to parse registration results '{}': {err}",
+            output_file.display()
+        );
+        AppError::new(AppErrorKind::JsonDeError, error_msg)
+    })
+}
+
+fn same_file(a: &path::Path, b: &path::Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Keygen outputs are `*.json` files, other than receipts and the results file
+fn is_keygen_output_file(path: &path::Path, output_file: &path::Path) -> bool {
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+    let is_receipt = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".receipt.json"));
+    is_json && !is_receipt && !same_file(path, output_file)
+}
+
+/// Read every keygen output in `input_dir`, sorted by file name.
+/// Other JSON files that do not parse as keygen outputs are skipped
+fn read_keygen_outputs(
+    input_dir: &path::Path,
+    output_file: &path::Path,
+) -> AppResult<Vec<(path::PathBuf, BlsKeygenOutput)>> {
+    let mut dirlist: Vec<path::PathBuf> = std::fs::read_dir(input_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_keygen_output_file(path, output_file))
+        .collect();
+    dirlist.sort();
+
+    let mut keygen_outputs = Vec::with_capacity(dirlist.len());
+    for path in dirlist {
+        match read_keygen_output(&path) {
+            Ok(keygen_data) => keygen_outputs.push((path, keygen_data)),
+            Err(_) => println!("Skipping '{}': not a keygen output", path.display()),
+        }
+    }
+    Ok(keygen_outputs)
+}
+
+/// Transaction of a previous run that was sent but not confirmed
+fn pending_tx_hash(result: &RegistrationResult) -> Option<H256> {
+    if result.status != RegistrationStatus::Sent {
+        return None;
+    }
+    result
+        .tx_hash
+        .as_deref()
+        .and_then(|tx_hash| strip_0x_prefix(tx_hash).parse().ok())
+}
+
+fn normalize_pubkey(pubkey: &str) -> String {
+    strip_0x_prefix(pubkey).to_lowercase()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn register_validator_keys(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    module_name: &str,
+    number_of_days: u64,
+    input_dir: &path::Path,
+    output_file: &path::Path,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+
+    let module_name = ModuleName::parse(module_name)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let keygen_outputs = read_keygen_outputs(input_dir, output_file)?;
+    println!("Found {} keygen outputs", keygen_outputs.len());
+
+    // pre-flight checks for the whole batch
+    let mut seen_pubkeys = HashSet::new();
+    for (path, keygen_data) in keygen_outputs.iter() {
+        if !seen_pubkeys.insert(normalize_pubkey(&keygen_data.bls_pub_key)) {
+            let error_msg = format!(
+                "Duplicate BLS public key '{}' in '{}'",
+                keygen_data.bls_pub_key,
+                path.display()
+            );
+            return Err(AppError::new(AppErrorKind::ParseError, error_msg));
+        }
+        if keygen_data.module_name != module_name {
+            let error_msg = format!(
+                "'{}' was generated for module '{}'",
+                path.display(),
+                keygen_data.module_name
+            );
+            return Err(AppError::new(AppErrorKind::ParseError, error_msg));
+        }
+        validator_key_data(keygen_data)?;
+    }
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_oracle_contract: PufferOracle<_> =
+        PufferOracle::new(contracts.puffer_oracle, client.clone());
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    let vt_price: U256 = puffer_oracle_contract
+        .get_validator_ticket_price()
+        .await
+        .map_err(|err| {
+            let error_msg = format!("Failed to fetch vt price: {err}");
+            AppError::new(AppErrorKind::ContractCallError, error_msg)
+        })?;
+
+    println!("Running pre-flight checks...");
+    let module_state =
+        ModuleRegistrationState::fetch(&puffer_protocol_contract, module_name).await?;
+    println!("Module address: {:?}", module_state.module_address);
+
+    // keys with a receipt or a transaction sent by a previous run are not sent again
+    let mut manifest = read_manifest(output_file)?;
+    let mut pending = Vec::with_capacity(keygen_outputs.len());
+    let mut sent = Vec::new();
+    for (path, keygen_data) in keygen_outputs.into_iter() {
+        let pubkey = keygen_data.bls_pub_key.clone();
+        let receipt_path = RegistrationReceipt::path_for(&path);
+        let previous_tx_hash = manifest.get(&pubkey).and_then(pending_tx_hash);
+
+        if receipt_path.exists() {
+            println!(
+                "Skipping '{pubkey}': receipt '{}' exists",
+                receipt_path.display()
+            );
+            let receipt = RegistrationReceipt::read(&receipt_path).ok();
+            manifest.insert(
+                pubkey,
+                RegistrationResult {
+                    input_file: path.display().to_string(),
+                    status: RegistrationStatus::Confirmed,
+                    tx_hash: receipt
+                        .as_ref()
+                        .map(|receipt| strip_0x_prefix(&receipt.tx_hash).to_string()),
+                    puffer_module_index: receipt.map(|receipt| receipt.puffer_module_index),
+                    error: None,
+                },
+            );
+        } else if let Some(tx_hash) = previous_tx_hash {
+            println!(
+                "Skipping '{pubkey}': transaction '0x{}' was already sent",
+                hex::encode(tx_hash)
+            );
+            match utils::ethereum::get_transaction(&provider, tx_hash).await {
+                Ok(tx) => sent.push((pubkey, path, tx_hash, tx.value)),
+                Err(err) => {
+                    println!("{pubkey}: {err}");
+                    if let Some(registration_result) = manifest.get_mut(&pubkey) {
+                        registration_result.status = RegistrationStatus::Failed;
+                        registration_result.error = Some(err.to_string());
+                    }
+                }
+            }
+        } else if module_state.is_registered(&pubkey) {
+            println!("Skipping '{pubkey}': already registered");
+            manifest.insert(
+                pubkey,
+                RegistrationResult {
+                    input_file: path.display().to_string(),
+                    status: RegistrationStatus::AlreadyRegistered,
+                    tx_hash: None,
+                    puffer_module_index: None,
+                    error: None,
+                },
+            );
+        } else {
+            pending.push((path, keygen_data));
+        }
+    }
+    write_manifest(output_file, &manifest)?;
+
+    let pending_keys: Vec<&BlsKeygenOutput> =
+        pending.iter().map(|(_, keygen_data)| keygen_data).collect();
+    module_state.check_keys(&pending_keys)?;
+
+    let total_value = pending
+        .iter()
+        .fold(U256::zero(), |total, (_, keygen_data)| {
+            total.saturating_add(registration_value(keygen_data, vt_price, number_of_days))
+        });
+    let balance = utils::ethereum::get_balance(
+        &provider,
+        wallet.address(),
+        BlockId::Number(BlockNumber::Latest),
+    )
+    .await?;
+    if balance < total_value {
+        let error_msg = format!(
+            "Insufficient balance to register {} keys: {} wei needed, {} wei available",
+            pending.len(),
+            total_value,
+            balance
+        );
+        return Err(AppError::new(AppErrorKind::ContractCallError, error_msg));
+    }
+
+    let mut nonce = match tx_options.nonce {
+        Some(nonce) => nonce,
+        None => utils::ethereum::get_nonce(&provider, wallet.address()).await?,
+    };
+
+    println!("Registering {} keys...", pending.len());
+    for (path, keygen_data) in pending.into_iter() {
+        let validator_data = validator_key_data(&keygen_data)?;
+        let value = registration_value(&keygen_data, vt_price, number_of_days);
+        let function_call = puffer_protocol_contract
+            .register_validator_key(
+                validator_data,
+                module_name.to_bytes(),
+                empty_permit(),
+                empty_permit(),
+            )
+            .value(value);
+
+        let options = TransactionOptions {
+            nonce: Some(nonce),
+            ..tx_options.clone()
+        };
+        let result = utils::transaction::send_transaction(
+            client.as_ref(),
+            function_call.tx,
+            &crate::contract_abis(),
+            &options,
+        )
+        .await;
+
+        let registration_result = match result {
+            Ok(tx_hash) => {
+                nonce += U256::one();
+                let tx_hash_str = hex::encode(tx_hash);
+                println!("{}: Tx Hash: '{tx_hash_str}'", keygen_data.bls_pub_key);
+                sent.push((
+                    keygen_data.bls_pub_key.clone(),
+                    path.clone(),
+                    tx_hash,
+                    value,
+                ));
+                RegistrationResult {
+                    input_file: path.display().to_string(),
+                    status: RegistrationStatus::Sent,
+                    tx_hash: Some(tx_hash_str),
+                    puffer_module_index: None,
+                    error: None,
+                }
+            }
+            Err(err) => {
+                println!("{}: {err}", keygen_data.bls_pub_key);
+                RegistrationResult {
+                    input_file: path.display().to_string(),
+                    status: RegistrationStatus::Failed,
+                    tx_hash: None,
+                    puffer_module_index: None,
+                    error: Some(err.to_string()),
+                }
+            }
+        };
+        manifest.insert(keygen_data.bls_pub_key.clone(), registration_result);
+        write_manifest(output_file, &manifest)?;
+    }
+
+    println!(
+        "Waiting for {} confirmation(s)...",
+        tx_options.confirmations
+    );
+    for (pubkey, path, tx_hash, value) in sent.into_iter() {
+        let receipt = utils::transaction::wait_for_transaction(
+            client.as_ref(),
+            tx_hash,
+            tx_options.confirmations,
+        )
+        .await;
+        if let Some(registration_result) = manifest.get_mut(&pubkey) {
+            match receipt {
+                Ok(receipt) => {
+                    match RegistrationReceipt::from_receipt(
+                        &receipt,
+                        puffer_protocol_address_h160,
+                        value,
+                    ) {
+                        Ok(registration_receipt) => {
+                            registration_result.status = RegistrationStatus::Confirmed;
+                            registration_result.puffer_module_index =
+                                Some(registration_receipt.puffer_module_index);
+                            let receipt_path = RegistrationReceipt::path_for(&path);
+                            if let Err(err) = registration_receipt.write(&receipt_path) {
+                                let error_msg = format!(
+                                    "Failed to write receipt '{}': {err}",
+                                    receipt_path.display()
+                                );
+                                println!("{pubkey}: {error_msg}");
+                                registration_result.error = Some(error_msg);
+                            }
+                        }
+                        Err(err) => {
+                            registration_result.status = RegistrationStatus::Reverted;
+                            registration_result.error = Some(err.to_string());
+                        }
+                    }
+                }
+                Err(err) => {
+                    registration_result.error = Some(err.to_string());
+                }
+            }
+            println!("{pubkey}: {:?}", registration_result.status);
+        }
+        write_manifest(output_file, &manifest)?;
+    }
+
+    let failed = manifest
+        .values()
+        .filter(|result| {
+            !matches!(
+                result.status,
+                RegistrationStatus::Confirmed | RegistrationStatus::AlreadyRegistered
+            )
+        })
+        .count();
+    println!("Results written to '{}'", output_file.display());
+    if failed > 0 {
+        println!("{failed} key(s) not registered, rerun to retry");
+        return Ok(1);
+    }
+    Ok(0)
+}
@@ -0,0 +1,119 @@
+use std::io::Write;
+use std::path;
+
+use axum::http::StatusCode;
+use ethers::contract::parse_log;
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult, ServerErrorCode, ServerErrorResponse};
+use coral_lib::structs::ModuleName;
+
+use crate::{ValidatorKeyRegisteredFilter, ValidatorTicketsDepositedFilter};
+
+/// Outcome of a `registerValidatorKey` transaction, written next to the keygen output
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegistrationReceipt {
+    pub bls_pub_key: String,
+    pub module_name: ModuleName,
+    pub puffer_module_index: u64,
+    pub using_enclave: bool,
+    pub node: String,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub eth_paid: String,
+    pub vt_deposited: String,
+    pub gas_fee: String,
+}
+
+impl RegistrationReceipt {
+    /// Decode `ValidatorKeyRegistered` and `ValidatorTicketsDeposited`
+    /// emitted by PufferProtocol in the transaction receipt
+    pub fn from_receipt(
+        receipt: &TransactionReceipt,
+        puffer_protocol_address: Address,
+        eth_paid: U256,
+    ) -> AppResult<Self> {
+        if receipt.status != Some(U64::one()) {
+            let error_msg = format!(
+                "Transaction '0x{}' reverted",
+                hex::encode(receipt.transaction_hash)
+            );
+            let err = ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::EvmSmartContractRevert,
+                error_msg,
+            );
+            return Err(err.into());
+        }
+
+        let protocol_logs = receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == puffer_protocol_address);
+
+        let mut registered: Option<ValidatorKeyRegisteredFilter> = None;
+        let mut vt_deposited = U256::zero();
+        for log in protocol_logs {
+            if let Ok(event) = parse_log::<ValidatorKeyRegisteredFilter>(log.clone()) {
+                registered = Some(event);
+            } else if let Ok(event) = parse_log::<ValidatorTicketsDepositedFilter>(log.clone()) {
+                vt_deposited = vt_deposited.saturating_add(event.amount);
+            }
+        }
+
+        let registered = registered.ok_or_else(|| {
+            let error_msg = "No ValidatorKeyRegistered event in transaction receipt";
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ServerErrorCode::EvmFetchLogError,
+                error_msg.to_string(),
+            )
+        })?;
+
+        let puffer_module_index = u64::try_from(registered.puffer_module_index).map_err(|_| {
+            let error_msg = format!(
+                "Invalid puffer module index {} in ValidatorKeyRegistered event",
+                registered.puffer_module_index
+            );
+            AppError::new(AppErrorKind::DecodeError, error_msg)
+        })?;
+
+        let gas_fee = receipt
+            .gas_used
+            .unwrap_or_default()
+            .saturating_mul(receipt.effective_gas_price.unwrap_or_default());
+
+        Ok(Self {
+            bls_pub_key: format!("0x{}", hex::encode(&registered.pub_key)),
+            module_name: ModuleName::new(registered.module_name),
+            puffer_module_index,
+            using_enclave: registered.using_enclave,
+            node: format!("{:?}", receipt.from),
+            block_number: receipt.block_number.unwrap_or_default().as_u64(),
+            tx_hash: format!("{:?}", receipt.transaction_hash),
+            eth_paid: eth_paid.to_string(),
+            vt_deposited: vt_deposited.to_string(),
+            gas_fee: gas_fee.to_string(),
+        })
+    }
+
+    /// `registration_001.json` -> `registration_001.receipt.json`
+    pub fn path_for(input_file: &path::Path) -> path::PathBuf {
+        input_file.with_extension("receipt.json")
+    }
+
+    pub fn read(input_file: &path::Path) -> AppResult<Self> {
+        let content = std::fs::read_to_string(input_file)?;
+        let receipt = serde_json::from_str(&content)?;
+        Ok(receipt)
+    }
+
+    pub fn write(&self, output_file: &path::Path) -> AppResult {
+        let json_string_pretty = serde_json::to_string_pretty(self)?;
+        let mut file = std::fs::File::create(output_file)?;
+        file.write_all(json_string_pretty.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use ethers::utils::hex;
+use serde::Serialize;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::structs::ModuleName;
+use coral_lib::utils;
+use coral_lib::utils::multicall::{BatchReader, CallBatch};
+
+use crate::commands::contract_call_error;
+use crate::PufferProtocol;
+
+/// Validator status as stored by `PufferProtocol`.
+/// Exited validators are deleted, so they read back as uninitialized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    Pending,
+    Skipped,
+    Active,
+    Exited,
+    NotRegistered,
+}
+
+impl ValidatorStatus {
+    fn from_contract(status: u8, index: u64, pending_index: u64) -> Self {
+        match status {
+            1 => Self::Pending,
+            2 => Self::Skipped,
+            3 => Self::Active,
+            _ if index < pending_index => Self::Exited,
+            _ => Self::NotRegistered,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidatorStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            Self::Pending => "pending",
+            Self::Skipped => "skipped",
+            Self::Active => "active",
+            Self::Exited => "exited",
+            Self::NotRegistered => "not registered",
+        };
+        write!(f, "{status}")
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidatorStatusOutput {
+    pub module_name: ModuleName,
+    pub puffer_module_index: u64,
+    pub status: ValidatorStatus,
+    pub bls_pub_key: String,
+    pub node: Address,
+    pub module: Address,
+    /// in wei, as a decimal string
+    pub bond: String,
+    pub pending_validator_index: u64,
+    pub next_validator_to_be_provisioned_index: u64,
+    /// Pending validators of the same module provisioned before this one
+    pub validators_ahead_in_module: Option<u64>,
+    /// Provisionings across all modules before this one, following the module weights
+    pub estimated_provisionings_ahead: Option<u64>,
+}
+
+/// Walk the module weights the way `PufferProtocol` picks the next validator,
+/// starting at the module currently selected, and count how many validators
+/// get provisioned before `validators_ahead` validators of `module_name` are done.
+fn estimate_provisionings_ahead(
+    module_weights: &[[u8; 32]],
+    start_module: [u8; 32],
+    queued: &HashMap<[u8; 32], u64>,
+    module_name: [u8; 32],
+    validators_ahead: u64,
+) -> Option<u64> {
+    let start = module_weights
+        .iter()
+        .position(|name| *name == start_module)
+        .unwrap_or_default();
+
+    let mut queued = queued.clone();
+    let mut ahead_in_module = validators_ahead;
+    let mut provisionings = 0;
+    let mut idle_rounds = 0;
+    let mut i = start;
+    while idle_rounds < module_weights.len() {
+        let name = module_weights[i % module_weights.len()];
+        i += 1;
+        let remaining = queued.entry(name).or_default();
+        if *remaining == 0 {
+            idle_rounds += 1;
+            continue;
+        }
+        idle_rounds = 0;
+        if name == module_name {
+            if ahead_in_module == 0 {
+                return Some(provisionings);
+            }
+            ahead_in_module -= 1;
+        }
+        *remaining -= 1;
+        provisionings += 1;
+    }
+    None
+}
+
+pub async fn validator_status(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    module_name: &str,
+    index: Option<u64>,
+    bls_pubkey: Option<&str>,
+    json: bool,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let module_name = ModuleName::parse(module_name)?;
+    let module_name_bytes = module_name.to_bytes();
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::generate_random_wallet();
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    let index = match (index, bls_pubkey) {
+        (Some(index), _) => index,
+        (None, Some(bls_pubkey)) => {
+            let bls_pubkey = coral_lib::strip_0x_prefix(bls_pubkey).to_lowercase();
+            let validators = puffer_protocol_contract
+                .get_validators(module_name_bytes)
+                .await
+                .map_err(contract_call_error("validators"))?;
+            validators
+                .iter()
+                .position(|validator| hex::encode(&validator.pub_key) == bls_pubkey)
+                .ok_or_else(|| {
+                    let error_msg =
+                        format!("Validator '0x{bls_pubkey}' not found in module '{module_name}'");
+                    AppError::new(AppErrorKind::AppError, error_msg)
+                })? as u64
+        }
+        (None, None) => {
+            let error_msg = "Either an index or a BLS public key is required";
+            return Err(AppError::new(
+                AppErrorKind::ParseError,
+                error_msg.to_string(),
+            ));
+        }
+    };
+
+    // the module weights are only needed for pending validators,
+    // but reading them in the same batch is cheaper than another round trip
+    let batch_reader = BatchReader::new(client.clone()).await?;
+    let mut batch = CallBatch::new();
+    let validator = batch
+        .add(puffer_protocol_contract.get_validator_info(module_name_bytes, U256::from(index)));
+    let pending_index =
+        batch.add(puffer_protocol_contract.get_pending_validator_index(module_name_bytes));
+    let next_to_be_provisioned_index = batch.add(
+        puffer_protocol_contract.get_next_validator_to_be_provisioned_index(module_name_bytes),
+    );
+    let module_weights = batch.add(puffer_protocol_contract.get_module_weights());
+    let next_to_provision = batch.add(puffer_protocol_contract.get_next_validator_to_provision());
+    let results = batch_reader.run(batch).await?;
+    let validator = results.get(validator)?;
+    let pending_index = results.get(pending_index)?.as_u64();
+    let next_to_be_provisioned_index = results.get(next_to_be_provisioned_index)?.as_u64();
+
+    let status = ValidatorStatus::from_contract(validator.status, index, pending_index);
+
+    let mut validators_ahead_in_module = None;
+    let mut estimated_provisionings_ahead = None;
+    if status == ValidatorStatus::Pending {
+        let validators_ahead = index.saturating_sub(next_to_be_provisioned_index);
+        validators_ahead_in_module = Some(validators_ahead);
+
+        let module_weights = results.get(module_weights)?;
+        let (next_module, _) = results.get(next_to_provision)?;
+
+        let mut module_names: Vec<[u8; 32]> = Vec::new();
+        for name in module_weights.iter() {
+            if !module_names.contains(name) {
+                module_names.push(*name);
+            }
+        }
+        let pending_indexes = batch_reader
+            .call(
+                module_names
+                    .iter()
+                    .map(|name| puffer_protocol_contract.get_pending_validator_index(*name))
+                    .collect(),
+            )
+            .await?;
+        let next_indexes = batch_reader
+            .call(
+                module_names
+                    .iter()
+                    .map(|name| {
+                        puffer_protocol_contract.get_next_validator_to_be_provisioned_index(*name)
+                    })
+                    .collect(),
+            )
+            .await?;
+        let queued: HashMap<[u8; 32], u64> = module_names
+            .iter()
+            .zip(pending_indexes.iter().zip(next_indexes.iter()))
+            .map(|(name, (pending, next))| (*name, pending.saturating_sub(*next).as_u64()))
+            .collect();
+
+        estimated_provisionings_ahead = estimate_provisionings_ahead(
+            &module_weights,
+            next_module,
+            &queued,
+            module_name_bytes,
+            validators_ahead,
+        );
+    }
+
+    let output = ValidatorStatusOutput {
+        module_name,
+        puffer_module_index: index,
+        status,
+        bls_pub_key: format!("0x{}", hex::encode(&validator.pub_key)),
+        node: validator.node,
+        module: validator.module,
+        bond: validator.bond.to_string(),
+        pending_validator_index: pending_index,
+        next_validator_to_be_provisioned_index: next_to_be_provisioned_index,
+        validators_ahead_in_module,
+        estimated_provisionings_ahead,
+    };
+
+    if json {
+        let output_json = serde_json::to_string_pretty(&output).map_err(|err| {
+            let error_msg = format!("Failed to serialize validator status: {err}");
+            AppError::new(AppErrorKind::ParseError, error_msg)
+        })?;
+        println!("{output_json}");
+        return Ok(0);
+    }
+
+    println!("Module: {}", output.module_name);
+    println!("Index: {index}");
+    println!("Status: {status}");
+    if status != ValidatorStatus::Exited && status != ValidatorStatus::NotRegistered {
+        println!("BLS public key: {}", output.bls_pub_key);
+        println!("Node: {:?}", output.node);
+        println!("Bond: {} wei", output.bond);
+    }
+    println!("Pending validator index: {pending_index}");
+    println!("Next validator to be provisioned index: {next_to_be_provisioned_index}");
+    if let Some(validators_ahead) = validators_ahead_in_module {
+        println!("Validators ahead in module: {validators_ahead}");
+    }
+    match estimated_provisionings_ahead {
+        Some(provisionings) => println!("Estimated provisionings before this one: {provisionings}"),
+        None if status == ValidatorStatus::Pending => {
+            println!("Estimated provisionings before this one: n/a (module not in weights)")
+        }
+        None => {}
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(module_name: &str) -> [u8; 32] {
+        ModuleName::parse(module_name).unwrap().to_bytes()
+    }
+
+    fn queued(queued: &[(&str, u64)]) -> HashMap<[u8; 32], u64> {
+        queued
+            .iter()
+            .map(|(module_name, count)| (name(module_name), *count))
+            .collect()
+    }
+
+    #[test]
+    fn wraps_around_module_weights() {
+        let weights = [name("A"), name("B"), name("C")];
+        let queued = queued(&[("A", 2), ("B", 1), ("C", 1)]);
+        // C, then A (ahead of us), B, C is empty, then our turn in A
+        let ahead = estimate_provisionings_ahead(&weights, name("C"), &queued, name("A"), 1);
+        assert_eq!(ahead, Some(3));
+    }
+
+    #[test]
+    fn skips_modules_without_queued_validators() {
+        let weights = [name("A"), name("B"), name("C")];
+        let empty = queued(&[("A", 0), ("C", 1)]);
+        let ahead = estimate_provisionings_ahead(&weights, name("A"), &empty, name("C"), 0);
+        assert_eq!(ahead, Some(0));
+
+        let one_in_b = queued(&[("A", 0), ("B", 1), ("C", 1)]);
+        let ahead = estimate_provisionings_ahead(&weights, name("A"), &one_in_b, name("C"), 0);
+        assert_eq!(ahead, Some(1));
+    }
+
+    #[test]
+    fn follows_module_weights() {
+        let weights = [name("A"), name("A"), name("B")];
+        let queued = queued(&[("A", 3), ("B", 2)]);
+        // A, A, B (ahead of us), A, A is empty, then our turn in B
+        let ahead = estimate_provisionings_ahead(&weights, name("A"), &queued, name("B"), 1);
+        assert_eq!(ahead, Some(4));
+    }
+
+    #[test]
+    fn starts_at_first_module_when_unknown() {
+        let weights = [name("A"), name("B")];
+        let queued = queued(&[("A", 1), ("B", 1)]);
+        let ahead = estimate_provisionings_ahead(&weights, name("X"), &queued, name("B"), 0);
+        assert_eq!(ahead, Some(1));
+    }
+
+    #[test]
+    fn module_not_in_weights_is_never_provisioned() {
+        let weights = [name("A"), name("B")];
+        let queued = queued(&[("A", 1), ("B", 1), ("C", 1)]);
+        let ahead = estimate_provisionings_ahead(&weights, name("A"), &queued, name("C"), 0);
+        assert_eq!(ahead, None);
+        assert_eq!(
+            estimate_provisionings_ahead(&[], name("A"), &queued, name("A"), 0),
+            None
+        );
+    }
+}
@@ -27,7 +27,7 @@ pub async fn get_withdrawal_credentials(
         )
     })?;
 
-    let provider = utils::ethereum::get_provider(rpc_url)?;
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
     let wallet = utils::wallet::generate_random_wallet();
     let chain_id = utils::ethereum::get_chain_id(&provider).await?;
     let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
@@ -0,0 +1,127 @@
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+
+use coral_lib::error::AppResult;
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::tx::{approve_if_needed, send_and_wait};
+use crate::{Erc20, PufferVault};
+
+use super::{parse_amount, print_exchange_rates, print_pufeth_balance};
+
+pub async fn deposit_eth(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+    receiver: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let amount = parse_amount("ETH", amount)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let receiver = match receiver {
+        Some(receiver) => utils::ethereum::parse_address("receiver", receiver)?,
+        None => wallet.address(),
+    };
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let shares = puffer_vault_contract
+        .preview_deposit(amount)
+        .await
+        .map_err(contract_call_error("deposit preview"))?;
+
+    println!(
+        "Depositing {} ETH for ~{} pufETH to {receiver:?}...",
+        format_ether(amount),
+        format_ether(shares)
+    );
+    let function_call = puffer_vault_contract.deposit_eth(receiver).value(amount);
+    send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    print_pufeth_balance(&puffer_vault_contract, receiver).await
+}
+
+pub async fn deposit_asset(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+    receiver: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let amount = parse_amount("asset", amount)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let receiver = match receiver {
+        Some(receiver) => utils::ethereum::parse_address("receiver", receiver)?,
+        None => wallet.address(),
+    };
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    let asset_address = puffer_vault_contract
+        .asset()
+        .await
+        .map_err(contract_call_error("vault asset"))?;
+    let asset_contract: Erc20<_> = Erc20::new(asset_address, client.clone());
+    let asset_symbol = asset_contract
+        .symbol()
+        .await
+        .map_err(contract_call_error("vault asset symbol"))?;
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let shares = puffer_vault_contract
+        .preview_deposit(amount)
+        .await
+        .map_err(contract_call_error("deposit preview"))?;
+
+    let allowance = asset_contract
+        .allowance(wallet.address(), contracts.puffer_vault)
+        .await
+        .map_err(contract_call_error(&format!("{asset_symbol} allowance")))?;
+
+    let approve_call = asset_contract.approve(contracts.puffer_vault, amount);
+    let deposit_tx_options = approve_if_needed(
+        client.as_ref(),
+        approve_call.tx,
+        allowance,
+        amount,
+        &format!("{} {asset_symbol} for PufferVault", format_ether(amount)),
+        tx_options,
+    )
+    .await?;
+
+    println!(
+        "Depositing {} {asset_symbol} for ~{} pufETH to {receiver:?}...",
+        format_ether(amount),
+        format_ether(shares)
+    );
+    let function_call = puffer_vault_contract.deposit(amount, receiver);
+    send_and_wait(client.as_ref(), function_call.tx, &deposit_tx_options).await?;
+
+    print_pufeth_balance(&puffer_vault_contract, receiver).await
+}
@@ -0,0 +1,307 @@
+pub mod deposit;
+pub mod preview;
+pub mod withdraw;
+
+use axum::http::StatusCode;
+use clap::Subcommand;
+use ethers::prelude::*;
+use ethers::utils::{format_ether, parse_ether};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult, ServerErrorCode, ServerErrorResponse};
+use coral_lib::profile::Profile;
+
+use crate::commands::contract_call_error;
+use crate::commands::resolve;
+use crate::commands::tx::TxArgs;
+use crate::PufferVault;
+
+/// Parse an amount of ETH or pufETH, e.g. `1` or `0.25`
+pub fn parse_amount(what: &str, amount: &str) -> AppResult<U256> {
+    parse_ether(amount).map_err(|err| {
+        AppError::new(
+            AppErrorKind::ParseError,
+            format!("Invalid {what} amount '{amount}': {err}"),
+        )
+    })
+}
+
+/// Vault parameters bounding a redemption or withdrawal
+#[derive(Clone, Copy, Debug)]
+pub struct WithdrawalLimits {
+    pub exit_fee_basis_points: U256,
+    pub remaining_daily_withdrawal_limit: U256,
+}
+
+impl WithdrawalLimits {
+    pub async fn fetch<M: Middleware + 'static>(
+        puffer_vault_contract: &PufferVault<M>,
+    ) -> AppResult<Self> {
+        let exit_fee_basis_points = puffer_vault_contract
+            .get_exit_fee_basis_points()
+            .await
+            .map_err(contract_call_error("exit fee"))?;
+        let remaining_daily_withdrawal_limit = puffer_vault_contract
+            .get_remaining_assets_daily_withdrawal_limit()
+            .await
+            .map_err(contract_call_error("remaining daily withdrawal limit"))?;
+        Ok(Self {
+            exit_fee_basis_points,
+            remaining_daily_withdrawal_limit,
+        })
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Exit fee: {} bps ({:.2}%)",
+            self.exit_fee_basis_points,
+            self.exit_fee_basis_points.low_u128() as f64 / 100.0
+        );
+        println!(
+            "Remaining daily withdrawal limit: {} ETH",
+            format_ether(self.remaining_daily_withdrawal_limit)
+        );
+    }
+
+    /// Whether withdrawing `assets` would reach the daily limit
+    pub fn is_reached_by(&self, assets: U256) -> bool {
+        assets >= self.remaining_daily_withdrawal_limit
+    }
+
+    /// Fail when `assets` exceed what can still be withdrawn today,
+    /// the vault would revert the transaction
+    pub fn check(&self, assets: U256) -> AppResult<()> {
+        if assets > self.remaining_daily_withdrawal_limit {
+            let error_msg = format!(
+                "Withdrawing {} ETH exceeds the remaining daily withdrawal limit of {} ETH",
+                format_ether(assets),
+                format_ether(self.remaining_daily_withdrawal_limit)
+            );
+            return Err(ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::PufferVaultDailyWithdrawalLimitExceeded,
+                error_msg,
+            )
+            .into());
+        }
+        if self.is_reached_by(assets) {
+            println!("Warning: this withdrawal uses up the remaining daily withdrawal limit");
+        }
+        Ok(())
+    }
+}
+
+/// Print the pufETH exchange rates in both directions
+pub async fn print_exchange_rates<M: Middleware + 'static>(
+    puffer_vault_contract: &PufferVault<M>,
+) -> AppResult<()> {
+    let one = U256::exp10(18);
+    let assets_per_share = puffer_vault_contract
+        .convert_to_assets(one)
+        .await
+        .map_err(contract_call_error("pufETH to ETH rate"))?;
+    let shares_per_asset = puffer_vault_contract
+        .convert_to_shares(one)
+        .await
+        .map_err(contract_call_error("ETH to pufETH rate"))?;
+    println!("1 pufETH = {} ETH", format_ether(assets_per_share));
+    println!("1 ETH = {} pufETH", format_ether(shares_per_asset));
+    Ok(())
+}
+
+pub async fn print_pufeth_balance<M: Middleware + 'static>(
+    puffer_vault_contract: &PufferVault<M>,
+    owner: Address,
+) -> AppResult<i32> {
+    let balance = puffer_vault_contract
+        .balance_of(owner)
+        .await
+        .map_err(contract_call_error("pufETH balance"))?;
+    println!("pufETH balance of {owner:?}: {}", format_ether(balance));
+    Ok(0)
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum VaultCommand {
+    #[command(about = "Deposit ETH into PufferVault for pufETH")]
+    DepositEth {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of ETH")]
+        amount: String,
+        #[arg(long = "receiver")]
+        receiver: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Approve and deposit the vault asset (WETH) for pufETH")]
+    Deposit {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of the vault asset")]
+        amount: String,
+        #[arg(long = "receiver")]
+        receiver: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Show the pufETH minted for a deposit")]
+    PreviewDeposit {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of ETH")]
+        amount: String,
+    },
+    #[command(about = "Show the ETH received for redeeming pufETH, after the exit fee")]
+    PreviewRedeem {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "shares", help = "Amount of pufETH")]
+        shares: String,
+    },
+    #[command(about = "Redeem pufETH for WETH")]
+    Redeem {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "shares", help = "Amount of pufETH")]
+        shares: String,
+        #[arg(long = "receiver")]
+        receiver: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Withdraw an exact amount of WETH, burning pufETH")]
+    Withdraw {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of WETH")]
+        amount: String,
+        #[arg(long = "receiver")]
+        receiver: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+}
+
+impl VaultCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::DepositEth {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+                receiver,
+                tx_args,
+            } => {
+                deposit::deposit_eth(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                    receiver.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::Deposit {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+                receiver,
+                tx_args,
+            } => {
+                deposit::deposit_asset(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                    receiver.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::PreviewDeposit {
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+            } => {
+                preview::preview_deposit(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                )
+                .await?;
+            }
+            Self::PreviewRedeem {
+                rpc_url,
+                puffer_protocol_address,
+                shares,
+            } => {
+                preview::preview_redeem(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &shares,
+                )
+                .await?;
+            }
+            Self::Redeem {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                shares,
+                receiver,
+                tx_args,
+            } => {
+                withdraw::redeem(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &shares,
+                    receiver.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::Withdraw {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+                receiver,
+                tx_args,
+            } => {
+                withdraw::withdraw(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                    receiver.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+        }
+        Ok(0)
+    }
+}
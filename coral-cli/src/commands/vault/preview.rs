@@ -0,0 +1,74 @@
+use ethers::utils::format_ether;
+
+use coral_lib::error::AppResult;
+use coral_lib::utils;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::PufferVault;
+
+use super::{parse_amount, print_exchange_rates, WithdrawalLimits};
+
+pub async fn preview_deposit(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let amount = parse_amount("ETH", amount)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, provider.into());
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let shares = puffer_vault_contract
+        .preview_deposit(amount)
+        .await
+        .map_err(contract_call_error("deposit preview"))?;
+    println!(
+        "Depositing {} ETH mints {} pufETH",
+        format_ether(amount),
+        format_ether(shares)
+    );
+    Ok(0)
+}
+
+pub async fn preview_redeem(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    shares: &str,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let shares = parse_amount("pufETH", shares)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, provider.into());
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let limits = WithdrawalLimits::fetch(&puffer_vault_contract).await?;
+    limits.print();
+
+    let assets = puffer_vault_contract
+        .preview_redeem(shares)
+        .await
+        .map_err(contract_call_error("redeem preview"))?;
+    println!(
+        "Redeeming {} pufETH returns {} ETH after the exit fee",
+        format_ether(shares),
+        format_ether(assets)
+    );
+    if limits.is_reached_by(assets) {
+        println!("Warning: this redemption reaches the remaining daily withdrawal limit");
+    }
+    Ok(0)
+}
@@ -0,0 +1,109 @@
+use ethers::prelude::*;
+use ethers::utils::format_ether;
+
+use coral_lib::error::AppResult;
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::tx::send_and_wait;
+use crate::PufferVault;
+
+use super::{parse_amount, print_exchange_rates, print_pufeth_balance, WithdrawalLimits};
+
+pub async fn redeem(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    shares: &str,
+    receiver: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let shares = parse_amount("pufETH", shares)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let receiver = match receiver {
+        Some(receiver) => utils::ethereum::parse_address("receiver", receiver)?,
+        None => wallet.address(),
+    };
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let limits = WithdrawalLimits::fetch(&puffer_vault_contract).await?;
+    limits.print();
+
+    let assets = puffer_vault_contract
+        .preview_redeem(shares)
+        .await
+        .map_err(contract_call_error("redeem preview"))?;
+    limits.check(assets)?;
+
+    println!(
+        "Redeeming {} pufETH for {} WETH to {receiver:?}...",
+        format_ether(shares),
+        format_ether(assets)
+    );
+    let function_call = puffer_vault_contract.redeem(shares, receiver, wallet.address());
+    send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    print_pufeth_balance(&puffer_vault_contract, wallet.address()).await
+}
+
+pub async fn withdraw(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+    receiver: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let assets = parse_amount("WETH", amount)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let receiver = match receiver {
+        Some(receiver) => utils::ethereum::parse_address("receiver", receiver)?,
+        None => wallet.address(),
+    };
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let puffer_vault_contract: PufferVault<_> =
+        PufferVault::new(contracts.puffer_vault, client.clone());
+
+    print_exchange_rates(&puffer_vault_contract).await?;
+    let limits = WithdrawalLimits::fetch(&puffer_vault_contract).await?;
+    limits.print();
+    limits.check(assets)?;
+
+    let shares = puffer_vault_contract
+        .preview_withdraw(assets)
+        .await
+        .map_err(contract_call_error("withdraw preview"))?;
+
+    println!(
+        "Withdrawing {} WETH for {} pufETH including the exit fee to {receiver:?}...",
+        format_ether(assets),
+        format_ether(shares)
+    );
+    let function_call = puffer_vault_contract.withdraw(assets, receiver, wallet.address());
+    send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    print_pufeth_balance(&puffer_vault_contract, wallet.address()).await
+}
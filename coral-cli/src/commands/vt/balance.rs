@@ -0,0 +1,56 @@
+use coral_lib::error::AppResult;
+use coral_lib::utils;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::{PufferProtocol, ValidatorTicket};
+
+use super::{format_vt, vt_to_days};
+
+pub async fn get_validator_tickets_balance(
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    node: &str,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let node = utils::ethereum::parse_address("node", node)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::generate_random_wallet();
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    let deposited = puffer_protocol_contract
+        .get_validator_tickets_balance(node)
+        .await
+        .map_err(contract_call_error("VT balance"))?;
+    let node_info = puffer_protocol_contract
+        .get_node_info(node)
+        .await
+        .map_err(contract_call_error("node info"))?;
+
+    println!("Node: {node:?}");
+    println!("Deposited VT: {}", format_vt(deposited));
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let validator_ticket_contract: ValidatorTicket<_> =
+        ValidatorTicket::new(contracts.validator_ticket, client.clone());
+    let wallet_balance = validator_ticket_contract
+        .balance_of(node)
+        .await
+        .map_err(contract_call_error("VT balance"))?;
+    println!("Wallet VT: {}", format_vt(wallet_balance));
+
+    let active_validators = node_info.active_validator_count;
+    println!("Active validators: {active_validators}");
+    if active_validators > 0 {
+        let days_left = vt_to_days(deposited) / active_validators as f64;
+        println!("Days of tickets left: {days_left:.2}");
+    }
+    Ok(0)
+}
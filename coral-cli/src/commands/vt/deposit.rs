@@ -0,0 +1,77 @@
+use ethers::prelude::*;
+
+use coral_lib::error::AppResult;
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::tx::{approve_if_needed, send_and_wait};
+use crate::{Permit, PufferProtocol, ValidatorTicket};
+
+use super::{format_vt, parse_vt};
+
+pub async fn deposit_validator_tickets(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+    node: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let amount = parse_vt(amount)?;
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let node = match node {
+        Some(node) => utils::ethereum::parse_address("node", node)?,
+        None => wallet.address(),
+    };
+
+    let contracts =
+        puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160).await?;
+    let validator_ticket_contract: ValidatorTicket<_> =
+        ValidatorTicket::new(contracts.validator_ticket, client.clone());
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    let allowance = validator_ticket_contract
+        .allowance(wallet.address(), puffer_protocol_address_h160)
+        .await
+        .map_err(contract_call_error("VT allowance"))?;
+
+    let approve_call = validator_ticket_contract.approve(puffer_protocol_address_h160, amount);
+    let deposit_tx_options = approve_if_needed(
+        client.as_ref(),
+        approve_call.tx,
+        allowance,
+        amount,
+        &format!("{} for PufferProtocol", format_vt(amount)),
+        tx_options,
+    )
+    .await?;
+
+    let permit = Permit {
+        deadline: U256::zero(),
+        amount,
+        v: 0,
+        r: [0; 32],
+        s: [0; 32],
+    };
+
+    println!("Depositing {} for node {node:?}...", format_vt(amount));
+    let function_call = puffer_protocol_contract.deposit_validator_tickets(permit, node);
+    send_and_wait(client.as_ref(), function_call.tx, &deposit_tx_options).await?;
+
+    let balance = puffer_protocol_contract
+        .get_validator_tickets_balance(node)
+        .await
+        .map_err(contract_call_error("VT balance"))?;
+    println!("Deposited VT balance of {node:?}: {}", format_vt(balance));
+    Ok(0)
+}
@@ -0,0 +1,184 @@
+pub mod balance;
+pub mod deposit;
+pub mod purchase;
+pub mod withdraw;
+
+use clap::Subcommand;
+use ethers::types::U256;
+use ethers::utils::{format_ether, parse_ether};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::profile::Profile;
+
+use crate::commands::resolve;
+use crate::commands::tx::TxArgs;
+
+/// 1 VT (10^18 wei) pays for one validator for one day
+pub fn vt_to_days(amount: U256) -> f64 {
+    format_ether(amount).parse().unwrap_or_default()
+}
+
+pub fn format_vt(amount: U256) -> String {
+    format!("{amount} wei ({:.2} days)", vt_to_days(amount))
+}
+
+/// Parse an amount of VT, e.g. `30` or `1.5`
+pub fn parse_vt(amount: &str) -> AppResult<U256> {
+    parse_ether(amount).map_err(|err| {
+        AppError::new(
+            AppErrorKind::ParseError,
+            format!("Invalid VT amount '{amount}': {err}"),
+        )
+    })
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum VtCommand {
+    #[command(about = "Purchase validator tickets with ETH")]
+    Purchase {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(
+            long = "validator-ticket-address",
+            help = "Defaults to the ValidatorTicket of --puffer-protocol-address"
+        )]
+        validator_ticket_address: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "number-of-days")]
+        number_of_days: u64,
+        #[arg(long = "recipient")]
+        recipient: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Approve and deposit validator tickets into PufferProtocol")]
+    Deposit {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of VT, 1 VT = 1 validator day")]
+        amount: String,
+        #[arg(long = "node")]
+        node: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Withdraw validator tickets from PufferProtocol")]
+    Withdraw {
+        #[arg(long = "private-key")]
+        private_key: String,
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "amount", help = "Amount of VT, 1 VT = 1 validator day")]
+        amount: String,
+        #[arg(long = "recipient")]
+        recipient: Option<String>,
+        #[command(flatten)]
+        tx_args: TxArgs,
+    },
+    #[command(about = "Show the validator ticket balance of a node")]
+    Balance {
+        #[arg(long = "rpc-url")]
+        rpc_url: Option<String>,
+        #[arg(long = "puffer-protocol-address")]
+        puffer_protocol_address: Option<String>,
+        #[arg(long = "node")]
+        node: String,
+    },
+}
+
+impl VtCommand {
+    pub async fn execute(self, profile: Option<&Profile>) -> AppResult<i32> {
+        match self {
+            Self::Purchase {
+                private_key,
+                rpc_url,
+                validator_ticket_address,
+                puffer_protocol_address,
+                number_of_days,
+                recipient,
+                tx_args,
+            } => {
+                let validator_ticket_address = resolve::validator_ticket_address(
+                    validator_ticket_address,
+                    puffer_protocol_address.as_deref(),
+                    profile,
+                );
+                let puffer_protocol_address = match validator_ticket_address {
+                    Some(_) => None,
+                    None => Some(resolve::puffer_protocol_address(
+                        puffer_protocol_address,
+                        profile,
+                    )?),
+                };
+                purchase::purchase_validator_tickets(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    validator_ticket_address.as_deref(),
+                    puffer_protocol_address.as_deref(),
+                    number_of_days,
+                    recipient.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::Deposit {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+                node,
+                tx_args,
+            } => {
+                deposit::deposit_validator_tickets(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                    node.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::Withdraw {
+                private_key,
+                rpc_url,
+                puffer_protocol_address,
+                amount,
+                recipient,
+                tx_args,
+            } => {
+                withdraw::withdraw_validator_tickets(
+                    &private_key,
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &amount,
+                    recipient.as_deref(),
+                    &tx_args.to_options()?,
+                )
+                .await?;
+            }
+            Self::Balance {
+                rpc_url,
+                puffer_protocol_address,
+                node,
+            } => {
+                balance::get_validator_tickets_balance(
+                    &resolve::rpc_url(rpc_url, profile).await?,
+                    &resolve::puffer_protocol_address(puffer_protocol_address, profile)?,
+                    &node,
+                )
+                .await?;
+            }
+        }
+        Ok(0)
+    }
+}
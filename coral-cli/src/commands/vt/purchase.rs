@@ -0,0 +1,82 @@
+use ethers::prelude::*;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contract_call_error;
+use crate::commands::contracts::puffer_contracts;
+use crate::commands::tx::send_and_wait;
+use crate::{PufferOracle, ValidatorTicket};
+
+use super::format_vt;
+
+pub async fn purchase_validator_tickets(
+    private_key: &str,
+    rpc_url: &str,
+    validator_ticket_address: Option<&str>,
+    puffer_protocol_address: Option<&str>,
+    number_of_days: u64,
+    recipient: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+
+    let validator_ticket_address_h160 = match (validator_ticket_address, puffer_protocol_address) {
+        (Some(validator_ticket_address), _) => {
+            utils::ethereum::parse_address("Validator Ticket", validator_ticket_address)?
+        }
+        (None, Some(puffer_protocol_address)) => {
+            let puffer_protocol_address_h160 =
+                utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+            puffer_contracts(&provider, chain_id.as_u64(), puffer_protocol_address_h160)
+                .await?
+                .validator_ticket
+        }
+        (None, None) => {
+            let error_msg = "Missing --validator-ticket-address or --puffer-protocol-address";
+            return Err(AppError::new(
+                AppErrorKind::ParseError,
+                error_msg.to_string(),
+            ));
+        }
+    };
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let recipient = match recipient {
+        Some(recipient) => utils::ethereum::parse_address("recipient", recipient)?,
+        None => wallet.address(),
+    };
+
+    let validator_ticket_contract: ValidatorTicket<_> =
+        ValidatorTicket::new(validator_ticket_address_h160, client.clone());
+
+    let puffer_oracle_address = validator_ticket_contract
+        .puffer_oracle()
+        .await
+        .map_err(contract_call_error("Puffer Oracle address"))?;
+    let puffer_oracle_contract: PufferOracle<_> =
+        PufferOracle::new(puffer_oracle_address, client.clone());
+
+    let vt_price: U256 = puffer_oracle_contract
+        .get_validator_ticket_price()
+        .await
+        .map_err(contract_call_error("vt price"))?;
+    let total_price = vt_price.saturating_mul(U256::from(number_of_days));
+
+    println!("VT price: {vt_price} wei per day");
+    println!("Purchasing {number_of_days} days of VT for {total_price} wei...");
+    let function_call = validator_ticket_contract
+        .purchase_validator_ticket(recipient)
+        .value(total_price);
+    send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    let balance = validator_ticket_contract
+        .balance_of(recipient)
+        .await
+        .map_err(contract_call_error("VT balance"))?;
+    println!("VT balance of {recipient:?}: {}", format_vt(balance));
+    Ok(0)
+}
@@ -0,0 +1,57 @@
+use ethers::prelude::*;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::utils;
+use coral_lib::utils::transaction::TransactionOptions;
+
+use crate::commands::contract_call_error;
+use crate::commands::tx::send_and_wait;
+use crate::PufferProtocol;
+
+use super::{format_vt, parse_vt};
+
+pub async fn withdraw_validator_tickets(
+    private_key: &str,
+    rpc_url: &str,
+    puffer_protocol_address: &str,
+    amount: &str,
+    recipient: Option<&str>,
+    tx_options: &TransactionOptions,
+) -> AppResult<i32> {
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", puffer_protocol_address)?;
+    let amount = parse_vt(amount)?;
+    if amount > U256::from(u128::MAX >> 32) {
+        let error_msg = format!("VT amount too large: {amount} wei");
+        return Err(AppError::new(AppErrorKind::ParseError, error_msg));
+    }
+
+    let provider = utils::ethereum::get_provider(rpc_url).await?;
+    let wallet = utils::wallet::wallet_from_private_key(private_key)?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let client = utils::ethereum::get_client(provider.clone(), wallet.clone(), chain_id.as_u64());
+
+    let recipient = match recipient {
+        Some(recipient) => utils::ethereum::parse_address("recipient", recipient)?,
+        None => wallet.address(),
+    };
+
+    let puffer_protocol_contract: PufferProtocol<_> =
+        PufferProtocol::new(puffer_protocol_address_h160, client.clone());
+
+    println!("Withdrawing {} to {recipient:?}...", format_vt(amount));
+    let function_call =
+        puffer_protocol_contract.withdraw_validator_tickets(amount.as_u128(), recipient);
+    send_and_wait(client.as_ref(), function_call.tx, tx_options).await?;
+
+    let balance = puffer_protocol_contract
+        .get_validator_tickets_balance(wallet.address())
+        .await
+        .map_err(contract_call_error("VT balance"))?;
+    println!(
+        "Deposited VT balance of {:?}: {}",
+        wallet.address(),
+        format_vt(balance)
+    );
+    Ok(0)
+}
@@ -0,0 +1,85 @@
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::local_db::ProtocolEvent;
+
+/// Where matched events are delivered
+#[derive(Clone, Debug)]
+pub enum Hook {
+    /// Print the event as a JSON line
+    Stdout,
+    /// POST the event as JSON
+    Webhook(String),
+    /// Run a shell command with the event JSON on stdin and in `CORAL_EVENT`
+    Exec(String),
+}
+
+impl Hook {
+    pub async fn call(&self, http_client: &reqwest::Client, event: &ProtocolEvent) -> AppResult {
+        let event_json = serde_json::to_string(event)?;
+        match self {
+            Self::Stdout => {
+                println!("{event_json}");
+            }
+            Self::Webhook(url) => {
+                let response = http_client.post(url).json(event).send().await?;
+                if !response.status().is_success() {
+                    let error_msg = format!("Webhook '{url}' returned {}", response.status());
+                    return Err(AppError::new(AppErrorKind::ReqwestError, error_msg));
+                }
+            }
+            Self::Exec(command) => {
+                let command = command.clone();
+                tokio::task::spawn_blocking(move || run_command(&command, &event_json))
+                    .await
+                    .map_err(|err| {
+                        let error_msg = format!("Hook command did not complete: {err}");
+                        AppError::new(AppErrorKind::AppError, error_msg)
+                    })??
+            }
+        }
+        Ok(())
+    }
+}
+
+fn run_command(command: &str, event_json: &str) -> AppResult {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("CORAL_EVENT", event_json)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the command may exit without reading stdin, the event is in CORAL_EVENT too
+        match stdin.write_all(event_json.as_bytes()) {
+            Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(err.into()),
+            _ => {}
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        let error_msg = format!("Hook command '{command}' failed: {status}");
+        return Err(AppError::new(AppErrorKind::AppError, error_msg));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_may_ignore_stdin() {
+        // larger than a pipe buffer and small enough for CORAL_EVENT,
+        // the write fails once the command exits
+        let event_json = "x".repeat(96 * 1024);
+        run_command("exit 0", &event_json).unwrap();
+    }
+
+    #[test]
+    fn failing_command_is_an_error() {
+        let err = run_command("cat > /dev/null; exit 3", "{}").unwrap_err();
+        assert!(err.to_string().contains("failed"), "{err}");
+    }
+}
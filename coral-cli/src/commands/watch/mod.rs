@@ -0,0 +1,224 @@
+pub mod hooks;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Args;
+use ethers::prelude::*;
+use futures_util::StreamExt;
+
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
+use coral_lib::local_db::{LocalDb, ProtocolEvent};
+use coral_lib::profile::Profile;
+use coral_lib::utils;
+
+use crate::commands::index::decode::tracked_event_signatures;
+use crate::commands::index::matcher::EventMatcher;
+use crate::commands::resolve;
+
+use self::hooks::Hook;
+
+#[derive(Clone, Debug, Args)]
+pub struct WatchArgs {
+    #[arg(long = "rpc-url")]
+    pub rpc_url: Option<String>,
+    #[arg(long = "puffer-protocol-address")]
+    pub puffer_protocol_address: Option<String>,
+    #[arg(long = "node", help = "Track validators registered by this node")]
+    pub nodes: Vec<String>,
+    #[arg(long = "bls-public-key", help = "Track this validator")]
+    pub bls_pubkeys: Vec<String>,
+    #[arg(
+        long = "db",
+        default_value = "coral-index.sqlite",
+        help = "Stores the last processed block, keys from `index sync` are tracked too"
+    )]
+    pub db: PathBuf,
+    #[arg(
+        long = "from-block",
+        help = "Block to start at on the first run (default: latest)"
+    )]
+    pub from_block: Option<u64>,
+    #[arg(long = "confirmations", default_value_t = 2)]
+    pub confirmations: u64,
+    #[arg(long = "chunk-size", default_value_t = 2000)]
+    pub chunk_size: u64,
+    #[arg(
+        long = "poll-interval",
+        default_value_t = 12,
+        help = "In seconds, unused over WebSocket and IPC. Failed RPC requests and hooks are retried after it"
+    )]
+    pub poll_interval: u64,
+    #[arg(long = "webhook-url", help = "POST each event as JSON to this URL")]
+    pub webhook_url: Option<String>,
+    #[arg(
+        long = "exec",
+        help = "Run this shell command for each event, with the event JSON on stdin and in CORAL_EVENT"
+    )]
+    pub exec: Option<String>,
+    #[arg(long = "quiet", help = "Don't print events to stdout")]
+    pub quiet: bool,
+}
+
+/// Progress key for watching a PufferProtocol deployment
+fn watch_progress_name(chain_id: U256, puffer_protocol_address: Address) -> String {
+    format!("watch:{chain_id}:{puffer_protocol_address:?}")
+}
+
+/// Run every hook on `event`, retrying a failed hook until it succeeds
+async fn deliver(
+    hooks: &[Hook],
+    http_client: &reqwest::Client,
+    event: &ProtocolEvent,
+    retry_interval: Duration,
+) {
+    for hook in hooks.iter() {
+        while let Err(err) = hook.call(http_client, event).await {
+            eprintln!(
+                "Hook failed for event {}:{}, retrying: {err}",
+                event.tx_hash, event.log_index
+            );
+            tokio::time::sleep(retry_interval).await;
+        }
+    }
+}
+
+pub async fn watch(args: WatchArgs, profile: Option<&Profile>) -> AppResult<i32> {
+    let rpc_url = resolve::rpc_url(args.rpc_url, profile).await?;
+    let puffer_protocol_address =
+        resolve::puffer_protocol_address(args.puffer_protocol_address, profile)?;
+    let puffer_protocol_address_h160 =
+        utils::ethereum::parse_address("Puffer Protocol", &puffer_protocol_address)?;
+    if args.chunk_size == 0 {
+        let error_msg = "Chunk size must be greater than 0";
+        return Err(AppError::new(
+            AppErrorKind::ParseError,
+            error_msg.to_string(),
+        ));
+    }
+    let nodes = args
+        .nodes
+        .iter()
+        .map(|node| utils::ethereum::parse_address("node", node))
+        .collect::<Result<Vec<Address>, _>>()?;
+
+    let mut hooks = Vec::new();
+    if !args.quiet {
+        hooks.push(Hook::Stdout);
+    }
+    if let Some(webhook_url) = args.webhook_url {
+        hooks.push(Hook::Webhook(webhook_url));
+    }
+    if let Some(exec) = args.exec {
+        hooks.push(Hook::Exec(exec));
+    }
+
+    let provider = utils::ethereum::get_provider(&rpc_url).await?;
+    let chain_id = utils::ethereum::get_chain_id(&provider).await?;
+    let http_client = reqwest::Client::new();
+
+    let mut db = LocalDb::open(args.db.as_path())?;
+    let progress_name = watch_progress_name(chain_id, puffer_protocol_address_h160);
+
+    let mut matcher = EventMatcher::new(&nodes);
+    for (pub_key, node) in db.get_registered_pubkeys()? {
+        matcher.add_pubkey(pub_key, Some(node));
+    }
+    for pub_key in args.bls_pubkeys.iter() {
+        let pub_key = coral_lib::add_0x_prefix(&pub_key.to_lowercase());
+        matcher.add_pubkey(pub_key, None);
+    }
+    if matcher.is_empty() {
+        let error_msg = "Nothing to watch, pass --node or --bls-public-key";
+        return Err(AppError::new(
+            AppErrorKind::ParseError,
+            error_msg.to_string(),
+        ));
+    }
+
+    let mut next_block = match db.get_progress(&progress_name)? {
+        Some(last_block) => last_block + 1,
+        None => match args.from_block {
+            Some(from_block) => from_block,
+            None => {
+                let latest = utils::ethereum::get_block_number(&provider).await?;
+                latest.saturating_sub(args.confirmations)
+            }
+        },
+    };
+    eprintln!("Watching from block {next_block}");
+
+    // over WebSocket and IPC, wake up on new blocks instead of polling
+    let mut new_heads = None;
+    if provider.as_ref().supports_subscriptions() {
+        match provider.subscribe_blocks().await {
+            Ok(stream) => new_heads = Some(stream),
+            Err(err) => eprintln!("Failed to subscribe to new blocks, polling instead: {err}"),
+        }
+    }
+
+    let poll_interval = Duration::from_secs(args.poll_interval);
+    'watch: loop {
+        let latest = match utils::ethereum::get_block_number(&provider).await {
+            Ok(latest) => latest,
+            Err(err) => {
+                eprintln!("Failed to fetch the latest block, retrying: {err}");
+                tokio::time::sleep(poll_interval).await;
+                continue;
+            }
+        };
+        let to_block = latest.saturating_sub(args.confirmations);
+        if to_block < next_block {
+            match new_heads.as_mut() {
+                Some(stream) => {
+                    if stream.next().await.is_none() {
+                        eprintln!("New block subscription closed, polling instead");
+                        new_heads = None;
+                    }
+                }
+                None => tokio::time::sleep(poll_interval).await,
+            }
+            continue;
+        }
+
+        let chunk_end = to_block.min(next_block + args.chunk_size - 1);
+        let filter = Filter::new()
+            .address(puffer_protocol_address_h160)
+            .topic0(tracked_event_signatures())
+            .from_block(next_block)
+            .to_block(chunk_end);
+        let logs = match utils::ethereum::get_logs(&provider, &filter).await {
+            Ok(logs) => logs,
+            Err(err) => {
+                eprintln!(
+                    "Failed to fetch logs of blocks {next_block}..={chunk_end}, retrying: {err}"
+                );
+                tokio::time::sleep(poll_interval).await;
+                continue;
+            }
+        };
+
+        for log in logs.iter() {
+            let event = match matcher.match_log(&provider, log).await {
+                Ok(Some(event)) => event,
+                Ok(None) => continue,
+                Err(err) => {
+                    // the range is processed again, delivered events are skipped
+                    eprintln!(
+                        "Failed to match log, retrying blocks {next_block}..={chunk_end}: {err}"
+                    );
+                    tokio::time::sleep(poll_interval).await;
+                    continue 'watch;
+                }
+            };
+            if db.is_delivered(&progress_name, &event)? {
+                continue;
+            }
+            deliver(&hooks, &http_client, &event, poll_interval).await;
+            db.insert_delivered_event(&event, &progress_name)?;
+        }
+
+        db.set_progress(&progress_name, chunk_end)?;
+        next_block = chunk_end + 1;
+    }
+}
@@ -8,21 +8,49 @@ use colored::Colorize;
 #[cfg(feature = "dev")]
 use ethers::contract::abigen;
 
-use coral_lib::error::AppResult;
+use coral_lib::error::{AppError, AppErrorKind, AppResult};
 
 use crate::commands::CommandArgs;
 
 pub const PROGRAM_NAME: &str = "coral-cli";
 
+#[cfg(feature = "dev")]
+abigen!(GuardianModule, "./abi/GuardianModule.json");
+#[cfg(feature = "dev")]
+abigen!(PufferModule, "./abi/PufferModule.json");
 #[cfg(feature = "dev")]
 abigen!(PufferOracle, "./abi/PufferOracleV2.json");
 #[cfg(feature = "dev")]
 abigen!(PufferProtocol, "./abi/PufferProtocol.json");
 #[cfg(feature = "dev")]
+abigen!(PufferVault, "./abi/PufferVaultV2.json");
+#[cfg(feature = "dev")]
 abigen!(ValidatorTicket, "./abi/ValidatorTicket.json");
+#[cfg(feature = "dev")]
+abigen!(
+    Erc20,
+    r#"[
+        function allowance(address owner, address spender) external view returns (uint256)
+        function approve(address spender, uint256 value) external returns (bool)
+        function symbol() external view returns (string)
+    ]"#
+);
 
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Bundled contract ABIs, used to decode custom errors from reverts
+#[cfg(feature = "dev")]
+pub fn contract_abis() -> [(&'static str, &'static ethers::abi::Abi); 6] {
+    [
+        ("PufferProtocol", &PUFFERPROTOCOL_ABI),
+        ("GuardianModule", &GUARDIANMODULE_ABI),
+        ("PufferModule", &PUFFERMODULE_ABI),
+        ("PufferOracle", &PUFFERORACLE_ABI),
+        ("PufferVault", &PUFFERVAULT_ABI),
+        ("ValidatorTicket", &VALIDATORTICKET_ABI),
+    ]
+}
+
 pub fn print_version() {
     println!("{PROGRAM_NAME}-{APP_VERSION}");
 }
@@ -33,14 +61,42 @@ async fn run_main(args: CommandArgs) -> AppResult<i32> {
         return Ok(0);
     }
 
+    let profile = match args.profile {
+        Some(name) => {
+            let path = args
+                .profiles_file
+                .or_else(coral_lib::profile::default_profiles_path)
+                .ok_or_else(|| {
+                    let error_msg = "No profiles file, pass --profiles-file";
+                    AppError::new(AppErrorKind::AppError, error_msg.to_string())
+                })?;
+            Some(coral_lib::profile::load_profile(&path, &name)?)
+        }
+        None => None,
+    };
+
+    #[cfg(feature = "dev")]
+    commands::init_rpc_config(args.rpc_timeout, args.rpc_max_retries, profile.as_ref());
+
     match args.subcommand {
-        Some(command) => commands::run_command(command).await,
+        Some(command) => commands::run_command(command, profile.as_ref()).await,
         None => Ok(0),
     }
 }
 
+/// Logs go to stderr, only when `RUST_LOG` is set
+fn init_tracing() {
+    if let Ok(filter) = tracing_subscriber::EnvFilter::try_from_default_env() {
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
+
 #[tokio::main]
 async fn main() {
+    init_tracing();
     let args = CommandArgs::parse();
 
     match run_main(args).await {
@@ -48,6 +104,9 @@ async fn main() {
         Err(err) => {
             let err_msg = format!("{}", err).red();
             eprintln!("{}", err_msg);
+            if let AppErrorKind::ServerError(error_code) = err.kind() {
+                eprintln!("Error code: {}", error_code.as_usize());
+            }
             process::exit(1);
         }
     }
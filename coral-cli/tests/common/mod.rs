@@ -0,0 +1,251 @@
+//! Local anvil node with the stand-in contracts of `tests/contracts/StandIns.sol` deployed.
+//! Needs `anvil` and `solc` on `PATH`, tests using it are `#[ignore]`d
+//! and run with `cargo test -p coral-cli --features dev -- --ignored`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethers::abi::Abi;
+use ethers::contract::ContractFactory;
+use ethers::core::utils::{Anvil, AnvilInstance};
+use ethers::prelude::*;
+use ethers::utils::hex;
+
+use coral_lib::structs::ModuleName;
+
+pub const MODULE_NAME: &str = "PUFFER_MODULE_0";
+pub const MODULE_LIMIT: u128 = 10;
+/// 0.01 ETH per validator day
+pub const VT_PRICE: u64 = 10_000_000_000_000_000;
+/// Uncompressed secp256k1 generator point, the guardian key of private key 1
+pub const GUARDIAN_PUBKEY: &str = "0x0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+pub const KEYSTORE_PASSWORD: &str = "coral-e2e-password";
+
+pub type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+struct CompiledContract {
+    abi: Abi,
+    bytecode: Bytes,
+}
+
+/// `solc --combined-json abi,bin`, keyed by contract name
+fn compile(source: &Path) -> HashMap<String, CompiledContract> {
+    let output = Command::new("solc")
+        .arg("--combined-json")
+        .arg("abi,bin")
+        .arg(source)
+        .output()
+        .expect("failed to run solc");
+    assert!(
+        output.status.success(),
+        "solc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let combined: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("invalid solc output");
+    let contracts = combined["contracts"]
+        .as_object()
+        .expect("missing contracts in solc output");
+    contracts
+        .iter()
+        .map(|(key, contract)| {
+            let name = key.rsplit(':').next().unwrap_or(key).to_string();
+            // solc before 0.8.10 returns the ABI as a JSON string
+            let abi = match &contract["abi"] {
+                serde_json::Value::String(abi) => serde_json::from_str(abi),
+                abi => serde_json::from_value(abi.clone()),
+            }
+            .expect("invalid ABI");
+            let bytecode = hex::decode(contract["bin"].as_str().expect("missing bytecode"))
+                .expect("invalid bytecode")
+                .into();
+            (name, CompiledContract { abi, bytecode })
+        })
+        .collect()
+}
+
+/// Temporary directory to run `coral-cli` in, removed on drop
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(test_name: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("coral-e2e-{test_name}-{nanos}"));
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// Run `coral-cli` in the directory, which is also `$HOME`
+    /// so the contracts cache and keystores stay out of the user's home
+    pub fn coral(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_coral-cli"))
+            .args(args)
+            .current_dir(&self.path)
+            .env("HOME", &self.path)
+            .env_remove("RUST_LOG")
+            .env_remove("CORAL_PROFILES")
+            .output()
+            .expect("failed to run coral-cli")
+    }
+
+    /// Run `coral-cli`, failing the test if it does not exit with 0
+    pub fn coral_ok(&self, args: &[&str]) -> String {
+        let output = self.coral(args);
+        assert!(
+            output.status.success(),
+            "coral-cli {} failed:\n{}\n{}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+pub struct Harness {
+    pub anvil: AnvilInstance,
+    pub client: Arc<Client>,
+    pub dir: TestDir,
+    pub puffer_protocol: Address,
+    pub puffer_oracle: Address,
+    pub validator_ticket: Address,
+    pub module_address: Address,
+}
+
+impl Harness {
+    /// Start anvil and deploy the stand-ins with one module.
+    /// Panics when `anvil` or `solc` is not installed
+    pub async fn start(test_name: &str) -> Self {
+        for tool in ["anvil", "solc"] {
+            assert!(
+                find_on_path(tool).is_some(),
+                "{test_name} needs '{tool}' on PATH"
+            );
+        }
+
+        let anvil = Anvil::new().spawn();
+        let provider = Provider::<Http>::try_from(anvil.endpoint())
+            .expect("invalid anvil endpoint")
+            .interval(std::time::Duration::from_millis(10));
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let client = Arc::new(SignerMiddleware::new(
+            provider,
+            wallet.with_chain_id(anvil.chain_id()),
+        ));
+
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/contracts/StandIns.sol");
+        let compiled = compile(&source);
+        let puffer_oracle =
+            deploy(&client, &compiled, "PufferOracleV2", U256::from(VT_PRICE)).await;
+        let validator_ticket = deploy(&client, &compiled, "ValidatorTicket", puffer_oracle).await;
+        let puffer_protocol = deploy(
+            &client,
+            &compiled,
+            "PufferProtocol",
+            (puffer_oracle, validator_ticket),
+        )
+        .await;
+
+        let module_address = Address::random();
+        let protocol = ethers::contract::Contract::new(
+            puffer_protocol,
+            compiled["PufferProtocol"].abi.clone(),
+            client.clone(),
+        );
+        let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+        protocol
+            .method::<_, ()>(
+                "createPufferModule",
+                (module_name, module_address, MODULE_LIMIT),
+            )
+            .unwrap()
+            .send()
+            .await
+            .expect("failed to send createPufferModule")
+            .await
+            .expect("failed to create module");
+
+        Self {
+            anvil,
+            client,
+            dir: TestDir::new(test_name),
+            puffer_protocol,
+            puffer_oracle,
+            validator_ticket,
+            module_address,
+        }
+    }
+
+    pub fn rpc_url(&self) -> String {
+        self.anvil.endpoint()
+    }
+
+    pub fn private_key(&self) -> String {
+        hex::encode(self.anvil.keys()[0].to_bytes())
+    }
+
+    pub fn node_address(&self) -> Address {
+        self.anvil.addresses()[0]
+    }
+
+    /// Run `coral-cli` in the test directory, see [`TestDir::coral`]
+    pub fn coral(&self, args: &[&str]) -> Output {
+        self.dir.coral(args)
+    }
+
+    /// Run `coral-cli`, failing the test if it does not exit with 0
+    pub fn coral_ok(&self, args: &[&str]) -> String {
+        self.dir.coral_ok(args)
+    }
+
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.dir.path(file_name)
+    }
+}
+
+async fn deploy<T: ethers::abi::Tokenize>(
+    client: &Arc<Client>,
+    compiled: &HashMap<String, CompiledContract>,
+    name: &str,
+    args: T,
+) -> Address {
+    let contract = &compiled[name];
+    let factory = ContractFactory::new(
+        contract.abi.clone(),
+        contract.bytecode.clone(),
+        client.clone(),
+    );
+    let contract = factory
+        .deploy(args)
+        .unwrap_or_else(|err| panic!("failed to encode {name} deployment: {err}"))
+        .send()
+        .await
+        .unwrap_or_else(|err| panic!("failed to deploy {name}: {err}"));
+    contract.address()
+}
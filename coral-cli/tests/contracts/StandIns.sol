@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// Minimal stand-ins for the Puffer contracts, with the functions, events and errors of the
+// bundled ABIs that coral-cli calls. There is no access control, guardian or
+// beacon deposit logic.
+
+contract PufferOracleV2 {
+    uint256 private _validatorTicketPrice;
+
+    event ValidatorTicketMintPriceUpdated(uint256 oldPrice, uint256 newPrice);
+
+    constructor(uint256 validatorTicketPrice) {
+        _validatorTicketPrice = validatorTicketPrice;
+    }
+
+    function getValidatorTicketPrice() external view returns (uint256) {
+        return _validatorTicketPrice;
+    }
+
+    function setMintPrice(uint256 newPrice) external {
+        emit ValidatorTicketMintPriceUpdated(_validatorTicketPrice, newPrice);
+        _validatorTicketPrice = newPrice;
+    }
+}
+
+contract ValidatorTicket {
+    PufferOracleV2 public immutable PUFFER_ORACLE;
+
+    uint256 public totalSupply;
+    mapping(address => uint256) public balanceOf;
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+
+    constructor(PufferOracleV2 oracle) {
+        PUFFER_ORACLE = oracle;
+    }
+
+    function purchaseValidatorTicket(address recipient) external payable returns (uint256) {
+        uint256 amount = (msg.value * 1 ether) / PUFFER_ORACLE.getValidatorTicketPrice();
+        totalSupply += amount;
+        balanceOf[recipient] += amount;
+        emit Transfer(address(0), recipient, amount);
+        return amount;
+    }
+}
+
+contract PufferProtocol {
+    struct ValidatorKeyData {
+        bytes blsPubKey;
+        bytes signature;
+        bytes32 depositDataRoot;
+        bytes[] blsEncryptedPrivKeyShares;
+        bytes blsPubKeySet;
+        bytes raveEvidence;
+    }
+
+    struct Permit {
+        uint256 deadline;
+        uint256 amount;
+        uint8 v;
+        bytes32 r;
+        bytes32 s;
+    }
+
+    struct Validator {
+        address node;
+        uint96 bond;
+        address module;
+        uint8 status;
+        bytes pubKey;
+    }
+
+    struct ModuleLimit {
+        uint128 allowedLimit;
+        uint128 numberOfRegisteredValidators;
+    }
+
+    // Status enum of the protocol: 0 = UNINITIALIZED, 1 = PENDING, 2 = SKIPPED, 3 = ACTIVE
+    uint8 private constant STATUS_ACTIVE = 3;
+
+    PufferOracleV2 public immutable PUFFER_ORACLE;
+    ValidatorTicket public immutable VALIDATOR_TICKET;
+    address public constant GUARDIAN_MODULE = address(0);
+    address public constant PUFFER_VAULT = address(0);
+    address public constant PUFFER_MODULE_MANAGER = address(0);
+    address public constant BEACON_DEPOSIT_CONTRACT = address(0);
+
+    bool public paused;
+    mapping(bytes32 => address) private _modules;
+    mapping(bytes32 => ModuleLimit) private _moduleLimits;
+    mapping(bytes32 => Validator[]) private _validators;
+    mapping(address => uint256) private _vtBalances;
+
+    event ValidatorKeyRegistered(
+        bytes pubKey, uint256 indexed pufferModuleIndex, bytes32 indexed moduleName, bool usingEnclave
+    );
+    event ValidatorTicketsDeposited(address indexed node, address indexed depositor, uint256 amount);
+
+    // the real PufferProtocol is AccessManaged, its functions revert with this while paused
+    error AccessManagedUnauthorized(address caller);
+    error InvalidAddress();
+    error ValidatorLimitForModuleReached();
+    error InvalidETHAmount();
+
+    constructor(PufferOracleV2 oracle, ValidatorTicket validatorTicket) {
+        PUFFER_ORACLE = oracle;
+        VALIDATOR_TICKET = validatorTicket;
+    }
+
+    function createPufferModule(bytes32 moduleName, address module, uint128 allowedLimit) external {
+        _modules[moduleName] = module;
+        _moduleLimits[moduleName].allowedLimit = allowedLimit;
+    }
+
+    function setPaused(bool isPaused) external {
+        paused = isPaused;
+    }
+
+    function revertIfPaused() external view {
+        if (paused) {
+            revert AccessManagedUnauthorized(msg.sender);
+        }
+    }
+
+    function getModuleAddress(bytes32 moduleName) external view returns (address) {
+        return _modules[moduleName];
+    }
+
+    function getModuleLimitInformation(bytes32 moduleName) external view returns (ModuleLimit memory) {
+        return _moduleLimits[moduleName];
+    }
+
+    function getWithdrawalCredentials(address module) public pure returns (bytes memory) {
+        return abi.encodePacked(bytes1(uint8(1)), bytes11(0), module);
+    }
+
+    function getValidators(bytes32 moduleName) external view returns (Validator[] memory) {
+        return _validators[moduleName];
+    }
+
+    function getValidatorInfo(bytes32 moduleName, uint256 pufferModuleIndex)
+        external
+        view
+        returns (Validator memory)
+    {
+        return _validators[moduleName][pufferModuleIndex];
+    }
+
+    function getValidatorTicketsBalance(address owner) external view returns (uint256) {
+        return _vtBalances[owner];
+    }
+
+    function registerValidatorKey(
+        ValidatorKeyData calldata data,
+        bytes32 moduleName,
+        Permit calldata,
+        Permit calldata
+    ) external payable {
+        if (paused) {
+            revert AccessManagedUnauthorized(msg.sender);
+        }
+        address module = _modules[moduleName];
+        if (module == address(0)) {
+            revert InvalidAddress();
+        }
+        ModuleLimit storage limit = _moduleLimits[moduleName];
+        if (limit.numberOfRegisteredValidators >= limit.allowedLimit) {
+            revert ValidatorLimitForModuleReached();
+        }
+        // duplicate keys are not checked, coral-cli rejects them before sending
+
+        bool usingEnclave = false;
+        if (data.raveEvidence.length > 0) {
+            (, bytes memory report,) = abi.decode(data.raveEvidence, (bytes, bytes, bytes));
+            usingEnclave = report.length > 0;
+        }
+        uint256 bond = usingEnclave ? 1 ether : 2 ether;
+        if (msg.value < bond) {
+            revert InvalidETHAmount();
+        }
+
+        if (msg.value > bond) {
+            uint256 vtAmount = VALIDATOR_TICKET.purchaseValidatorTicket{value: msg.value - bond}(address(this));
+            _vtBalances[msg.sender] += vtAmount;
+            emit ValidatorTicketsDeposited(msg.sender, msg.sender, vtAmount);
+        }
+
+        uint256 pufferModuleIndex = _validators[moduleName].length;
+        _validators[moduleName].push(
+            Validator({
+                node: msg.sender,
+                bond: uint96(bond),
+                module: module,
+                status: STATUS_ACTIVE,
+                pubKey: data.blsPubKey
+            })
+        );
+        limit.numberOfRegisteredValidators += 1;
+
+        emit ValidatorKeyRegistered(data.blsPubKey, pufferModuleIndex, moduleName, usingEnclave);
+    }
+}
@@ -0,0 +1,709 @@
+mod common;
+
+use ethers::contract::abigen;
+use ethers::prelude::*;
+use ethers::utils::{hex, parse_ether};
+
+use coral_lib::error::ServerErrorCode;
+use coral_lib::strip_0x_prefix;
+use coral_lib::structs::ModuleName;
+use coral_lib::utils::mock_enclave::{MockEnclave, MockEnclaveConfig, MockEvidence};
+use coral_lib::utils::transaction::{send_transaction, TransactionOptions};
+
+use common::{Harness, TestDir, GUARDIAN_PUBKEY, KEYSTORE_PASSWORD, MODULE_NAME};
+
+abigen!(PufferProtocol, "./abi/PufferProtocol.json");
+abigen!(
+    ValidatorTicket,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+    ]"#,
+);
+// stand-in functions that are not in the bundled ABI
+abigen!(
+    StandIns,
+    r#"[
+        function setPaused(bool isPaused) external
+        function createPufferModule(bytes32 moduleName, address module, uint128 allowedLimit) external
+    ]"#,
+);
+
+const NUMBER_OF_DAYS: &str = "30";
+const MOCK_PUBKEY: &str =
+    "0xebafd07b8235d5d2e5a4510e8141f72c7e1a3f88f1b8cf12063e0396a0c81a29bfbe207fe20edcd8c99ffd8b19a88b11";
+const FORK_VERSION: &str = "0x00000000";
+const GENESIS_VALIDATORS_ROOT: &str =
+    "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1";
+
+fn withdrawal_credentials(harness: &Harness) -> String {
+    let output = harness.coral_ok(&[
+        "validator",
+        "withdrawal-credentials",
+        "--rpc-url",
+        &harness.rpc_url(),
+        "--puffer-protocol-address",
+        &format!("{:?}", harness.puffer_protocol),
+        "--module-address",
+        &format!("{:?}", harness.module_address),
+    ]);
+    output.trim().to_string()
+}
+
+/// Non-enclave keygen, returns the keygen output file
+fn keygen(harness: &Harness, file_name: &str) -> String {
+    let password_file = harness.path("password.txt");
+    std::fs::write(&password_file, KEYSTORE_PASSWORD).unwrap();
+    let output_file = harness.path(file_name);
+
+    harness.coral_ok(&[
+        "validator",
+        "keygen",
+        "--guardian-pubkeys",
+        GUARDIAN_PUBKEY,
+        "--guardian-threshold",
+        "1",
+        "--module-name",
+        MODULE_NAME,
+        "--withdrawal-credentials",
+        &withdrawal_credentials(harness),
+        "--fork-version",
+        "0x00000000",
+        "--password-file",
+        password_file.to_str().unwrap(),
+        "--output-file",
+        output_file.to_str().unwrap(),
+    ]);
+    output_file.to_str().unwrap().to_string()
+}
+
+fn normalize_hex(value: &str) -> String {
+    strip_0x_prefix(value).to_lowercase()
+}
+
+fn read_json(path: &str) -> serde_json::Value {
+    let content = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&content).unwrap()
+}
+
+fn register_key_args<'a>(
+    private_key: &'a str,
+    rpc_url: &'a str,
+    puffer_protocol: &'a str,
+    input_file: &'a str,
+) -> Vec<&'a str> {
+    vec![
+        "validator",
+        "register-key",
+        "--private-key",
+        private_key,
+        "--rpc-url",
+        rpc_url,
+        "--puffer-protocol-address",
+        puffer_protocol,
+        "--module-name",
+        MODULE_NAME,
+        "--number-of-days",
+        NUMBER_OF_DAYS,
+        "--input-file",
+        input_file,
+    ]
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn contracts_are_read_from_puffer_protocol() {
+    let harness = Harness::start("contracts").await;
+
+    let output = harness.coral_ok(&[
+        "contracts",
+        "--rpc-url",
+        &harness.rpc_url(),
+        "--puffer-protocol-address",
+        &format!("{:?}", harness.puffer_protocol),
+        "--json",
+    ]);
+    let contracts: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        contracts["puffer_oracle"],
+        format!("{:?}", harness.puffer_oracle)
+    );
+    assert_eq!(
+        contracts["validator_ticket"],
+        format!("{:?}", harness.validator_ticket)
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn withdrawal_credentials_point_to_module() {
+    let harness = Harness::start("withdrawal-credentials").await;
+
+    let expected = format!(
+        "0x01{}{}",
+        "00".repeat(11),
+        hex::encode(harness.module_address)
+    );
+    assert_eq!(withdrawal_credentials(&harness), expected);
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn keygen_and_register_key() {
+    let harness = Harness::start("register-key").await;
+    let rpc_url = harness.rpc_url();
+    let private_key = harness.private_key();
+    let puffer_protocol = format!("{:?}", harness.puffer_protocol);
+
+    let input_file = keygen(&harness, "registration_001.json");
+    let keygen_output = read_json(&input_file);
+    let bls_pub_key = normalize_hex(keygen_output["bls_pub_key"].as_str().unwrap());
+    assert_eq!(keygen_output["intel_report"], "");
+
+    // calldata of the same registration, without sending it
+    let output = harness.coral_ok(&[
+        "validator",
+        "generate-register-calldata",
+        "--rpc-url",
+        &rpc_url,
+        "--puffer-protocol-address",
+        &puffer_protocol,
+        "--module-name",
+        MODULE_NAME,
+        "--input-file",
+        &input_file,
+    ]);
+    let calldata = output.lines().last().unwrap();
+    let selector = hex::encode(RegisterValidatorKeyCall::selector());
+    assert!(
+        calldata.starts_with(&format!("0x{selector}")),
+        "unexpected calldata '{calldata}'"
+    );
+
+    harness.coral_ok(&register_key_args(
+        &private_key,
+        &rpc_url,
+        &puffer_protocol,
+        &input_file,
+    ));
+
+    // state
+    let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+    let protocol = PufferProtocol::new(harness.puffer_protocol, harness.client.clone());
+    let validators = protocol.get_validators(module_name).call().await.unwrap();
+    assert_eq!(validators.len(), 1);
+    assert_eq!(hex::encode(&validators[0].pub_key), bls_pub_key);
+    assert_eq!(validators[0].node, harness.node_address());
+    assert_eq!(validators[0].module, harness.module_address);
+    assert_eq!(U256::from(validators[0].bond), parse_ether(2).unwrap());
+    // Status::ACTIVE of the protocol, see `ValidatorStatus::from_contract`
+    assert_eq!(validators[0].status, 3);
+
+    let limit = protocol
+        .get_module_limit_information(module_name)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(limit.number_of_registered_validators, 1);
+
+    let vt_amount = parse_ether(NUMBER_OF_DAYS).unwrap();
+    let validator_ticket = ValidatorTicket::new(harness.validator_ticket, harness.client.clone());
+    let vt_balance = validator_ticket
+        .balance_of(harness.puffer_protocol)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(vt_balance, vt_amount);
+    let node_vt_balance = protocol
+        .get_validator_tickets_balance(harness.node_address())
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(node_vt_balance, vt_amount);
+
+    // events
+    let registered = protocol
+        .validator_key_registered_filter()
+        .from_block(0)
+        .query()
+        .await
+        .unwrap();
+    assert_eq!(registered.len(), 1);
+    assert_eq!(hex::encode(&registered[0].pub_key), bls_pub_key);
+    assert_eq!(registered[0].puffer_module_index, U256::zero());
+    assert_eq!(registered[0].module_name, module_name);
+    assert!(!registered[0].using_enclave);
+
+    let deposited = protocol
+        .validator_tickets_deposited_filter()
+        .from_block(0)
+        .query()
+        .await
+        .unwrap();
+    assert_eq!(deposited.len(), 1);
+    assert_eq!(deposited[0].node, harness.node_address());
+    assert_eq!(deposited[0].amount, vt_amount);
+
+    // receipt written next to the keygen output
+    let receipt = read_json(input_file.replace(".json", ".receipt.json").as_str());
+    assert_eq!(
+        normalize_hex(receipt["bls_pub_key"].as_str().unwrap()),
+        bls_pub_key
+    );
+    assert_eq!(receipt["puffer_module_index"], 0);
+    assert_eq!(receipt["using_enclave"], false);
+    assert_eq!(receipt["vt_deposited"], vt_amount.to_string());
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn register_key_rejects_registered_key() {
+    let harness = Harness::start("register-key-twice").await;
+    let rpc_url = harness.rpc_url();
+    let private_key = harness.private_key();
+    let puffer_protocol = format!("{:?}", harness.puffer_protocol);
+
+    let input_file = keygen(&harness, "registration_001.json");
+    let args = register_key_args(&private_key, &rpc_url, &puffer_protocol, &input_file);
+    harness.coral_ok(&args);
+
+    let output = harness.coral(&args);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("already registered"),
+        "unexpected error: {stderr}"
+    );
+
+    let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+    let protocol = PufferProtocol::new(harness.puffer_protocol, harness.client.clone());
+    let validators = protocol.get_validators(module_name).call().await.unwrap();
+    assert_eq!(validators.len(), 1);
+}
+
+fn register_keys_args<'a>(
+    private_key: &'a str,
+    rpc_url: &'a str,
+    puffer_protocol: &'a str,
+    input_dir: &'a str,
+    output_file: &'a str,
+) -> Vec<&'a str> {
+    vec![
+        "validator",
+        "register-keys",
+        "--private-key",
+        private_key,
+        "--rpc-url",
+        rpc_url,
+        "--puffer-protocol-address",
+        puffer_protocol,
+        "--module-name",
+        MODULE_NAME,
+        "--number-of-days",
+        NUMBER_OF_DAYS,
+        "--input-dir",
+        input_dir,
+        "--output-file",
+        output_file,
+    ]
+}
+
+/// Keygen outputs in `keys/`, returns their BLS public keys
+fn keygen_dir(harness: &Harness, count: usize) -> Vec<String> {
+    std::fs::create_dir_all(harness.path("keys")).unwrap();
+    (1..=count)
+        .map(|i| {
+            let input_file = keygen(harness, &format!("keys/registration_{i:03}.json"));
+            read_json(&input_file)["bls_pub_key"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn register_keys_registers_every_key_once() {
+    let harness = Harness::start("register-keys").await;
+    let rpc_url = harness.rpc_url();
+    let private_key = harness.private_key();
+    let puffer_protocol = format!("{:?}", harness.puffer_protocol);
+    let input_dir = harness.path("keys");
+    let input_dir = input_dir.to_str().unwrap();
+    let output_file = harness.path("results.json");
+    let output_file = output_file.to_str().unwrap();
+
+    let pubkeys = keygen_dir(&harness, 2);
+    let args = register_keys_args(
+        &private_key,
+        &rpc_url,
+        &puffer_protocol,
+        input_dir,
+        output_file,
+    );
+    harness.coral_ok(&args);
+
+    let results = read_json(output_file);
+    let mut indices = Vec::new();
+    for pubkey in pubkeys.iter() {
+        let result = &results[pubkey];
+        assert_eq!(result["status"], "confirmed", "unexpected result {result}");
+        assert!(result["tx_hash"].is_string());
+        indices.push(result["puffer_module_index"].as_u64().unwrap());
+        let receipt_file = result["input_file"]
+            .as_str()
+            .unwrap()
+            .replace(".json", ".receipt.json");
+        assert!(std::path::Path::new(&receipt_file).exists());
+    }
+    indices.sort();
+    assert_eq!(indices, vec![0, 1]);
+
+    // a second run resumes from the receipts and sends nothing
+    let output = harness.coral_ok(&args);
+    assert!(
+        output.contains("Registering 0 keys"),
+        "unexpected output: {output}"
+    );
+    let rerun_results = read_json(output_file);
+    for pubkey in pubkeys.iter() {
+        assert_eq!(rerun_results[pubkey]["status"], "confirmed");
+        assert_eq!(rerun_results[pubkey]["tx_hash"], results[pubkey]["tx_hash"]);
+    }
+
+    let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+    let protocol = PufferProtocol::new(harness.puffer_protocol, harness.client.clone());
+    let validators = protocol.get_validators(module_name).call().await.unwrap();
+    assert_eq!(validators.len(), 2);
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn register_keys_skips_registered_keys() {
+    let harness = Harness::start("register-keys-registered").await;
+    let rpc_url = harness.rpc_url();
+    let private_key = harness.private_key();
+    let puffer_protocol = format!("{:?}", harness.puffer_protocol);
+    let input_dir = harness.path("keys");
+    let input_dir = input_dir.to_str().unwrap();
+    let output_file = harness.path("results.json");
+    let output_file = output_file.to_str().unwrap();
+
+    let pubkeys = keygen_dir(&harness, 2);
+    // register the first key on its own, without leaving a receipt behind
+    let first_file = harness.path("keys/registration_001.json");
+    let first_file = first_file.to_str().unwrap();
+    harness.coral_ok(&register_key_args(
+        &private_key,
+        &rpc_url,
+        &puffer_protocol,
+        first_file,
+    ));
+    std::fs::remove_file(first_file.replace(".json", ".receipt.json")).unwrap();
+
+    harness.coral_ok(&register_keys_args(
+        &private_key,
+        &rpc_url,
+        &puffer_protocol,
+        input_dir,
+        output_file,
+    ));
+
+    let results = read_json(output_file);
+    assert_eq!(results[&pubkeys[0]]["status"], "already_registered");
+    assert_eq!(results[&pubkeys[1]]["status"], "confirmed");
+    assert_eq!(results[&pubkeys[1]]["puffer_module_index"], 1);
+
+    let module_name = ModuleName::parse(MODULE_NAME).unwrap().to_bytes();
+    let protocol = PufferProtocol::new(harness.puffer_protocol, harness.client.clone());
+    let validators = protocol.get_validators(module_name).call().await.unwrap();
+    assert_eq!(validators.len(), 2);
+}
+
+/// Send a registration with the library, as `register-key` does after its pre-flight checks,
+/// and return the error of its simulation
+async fn simulate_registration(harness: &Harness, module_name: &str) -> String {
+    let protocol = PufferProtocol::new(harness.puffer_protocol, harness.client.clone());
+    let call = protocol
+        .register_validator_key(
+            ValidatorKeyData::default(),
+            ModuleName::parse(module_name).unwrap().to_bytes(),
+            Permit::default(),
+            Permit::default(),
+        )
+        .value(parse_ether(2).unwrap());
+    let err = send_transaction(
+        harness.client.as_ref(),
+        call.tx,
+        &[("PufferProtocol", &*PUFFERPROTOCOL_ABI)],
+        &TransactionOptions::default(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.response.result.error_code,
+        ServerErrorCode::EvmSmartContractRevert
+    );
+    err.to_string()
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn paused_revert_is_decoded() {
+    let harness = Harness::start("revert-paused").await;
+    let stand_ins = StandIns::new(harness.puffer_protocol, harness.client.clone());
+    stand_ins
+        .set_paused(true)
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+
+    let error_msg = simulate_registration(&harness, MODULE_NAME).await;
+    assert!(
+        error_msg
+            .starts_with("Transaction would revert: PufferProtocol.AccessManagedUnauthorized("),
+        "unexpected error: {error_msg}"
+    );
+    assert!(
+        error_msg.ends_with(
+            "): The caller is not allowed to call this function, or the protocol is paused"
+        ),
+        "unexpected error: {error_msg}"
+    );
+
+    // the CLI stops at its pre-flight check
+    let input_file = keygen(&harness, "registration_001.json");
+    let output = harness.coral(&register_key_args(
+        &harness.private_key(),
+        &harness.rpc_url(),
+        &format!("{:?}", harness.puffer_protocol),
+        &input_file,
+    ));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("PufferProtocol is paused"),
+        "unexpected error: {stderr}"
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs anvil and solc"]
+async fn validator_limit_revert_is_decoded() {
+    const FULL_MODULE_NAME: &str = "FULL_MODULE";
+    let harness = Harness::start("revert-limit").await;
+    let stand_ins = StandIns::new(harness.puffer_protocol, harness.client.clone());
+    stand_ins
+        .create_puffer_module(
+            ModuleName::parse(FULL_MODULE_NAME).unwrap().to_bytes(),
+            Address::random(),
+            0,
+        )
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+
+    let error_msg = simulate_registration(&harness, FULL_MODULE_NAME).await;
+    assert_eq!(
+        error_msg,
+        "Transaction would revert: PufferProtocol.ValidatorLimitForModuleReached(): \
+         The module has reached its validator limit"
+    );
+}
+
+/// Keygen payload returned by the mock, the fields of a real enclave's answer
+fn canned_keygen_payload() -> serde_json::Value {
+    serde_json::json!({
+        "bls_pub_key_set": "0x00",
+        "bls_pub_key": MOCK_PUBKEY,
+        "signature": format!("0x{}", "11".repeat(96)),
+        "deposit_data_root": format!("0x{}", "22".repeat(32)),
+        "bls_enc_priv_key_shares": ["0x33"],
+        "intel_sig": "0x44",
+        "intel_report": "report",
+        "intel_x509": "x509",
+        "guardian_eth_pub_keys": [GUARDIAN_PUBKEY],
+        "withdrawal_credentials": format!("0x01{}", "00".repeat(31)),
+        "fork_version": FORK_VERSION,
+    })
+}
+
+/// Tests using it run on a multi-thread runtime,
+/// so the mock keeps serving while a test waits for `coral-cli`
+async fn start_mock_enclave(keys: Vec<String>) -> MockEnclave {
+    let config = MockEnclaveConfig {
+        keys,
+        evidence: MockEvidence::Canned(canned_keygen_payload()),
+        healthy: true,
+    };
+    MockEnclave::start(config, "127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("failed to start mock enclave")
+}
+
+fn sign_voluntary_exit_args<'a>(
+    enclave_url: &'a str,
+    bls_pubkey: &'a str,
+    output_file: &'a str,
+) -> Vec<&'a str> {
+    vec![
+        "validator",
+        "sign-voluntary-exit",
+        "--enclave-url",
+        enclave_url,
+        "--bls-public-key",
+        bls_pubkey,
+        "--beacon-index",
+        "42",
+        "--fork-previous-version",
+        FORK_VERSION,
+        "--fork-current-version",
+        FORK_VERSION,
+        "--epoch",
+        "100",
+        "--genesis-validators-root",
+        GENESIS_VALIDATORS_ROOT,
+        "--output-file",
+        output_file,
+    ]
+}
+
+fn assert_failed(output: std::process::Output, expected: &str) {
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(expected), "unexpected error: {stderr}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_enclave_list_keys() {
+    let enclave = start_mock_enclave(vec![MOCK_PUBKEY.to_string()]).await;
+    let dir = TestDir::new("mock-list-keys");
+
+    let output = dir.coral_ok(&["validator", "list-keys", "--enclave-url", &enclave.url()]);
+    assert!(
+        output.contains(&format!("0: {MOCK_PUBKEY}")),
+        "unexpected output: {output}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_enclave_keygen() {
+    let enclave = start_mock_enclave(vec![]).await;
+    let dir = TestDir::new("mock-keygen");
+    let output_file = dir.path("registration_001.json");
+
+    dir.coral_ok(&[
+        "validator",
+        "keygen",
+        "--enclave-url",
+        &enclave.url(),
+        "--guardian-pubkeys",
+        GUARDIAN_PUBKEY,
+        "--guardian-threshold",
+        "1",
+        "--module-name",
+        MODULE_NAME,
+        "--withdrawal-credentials",
+        &format!("0x01{}", "00".repeat(31)),
+        "--fork-version",
+        FORK_VERSION,
+        "--output-file",
+        output_file.to_str().unwrap(),
+    ]);
+
+    let keygen_output = read_json(output_file.to_str().unwrap());
+    assert_eq!(keygen_output["bls_pub_key"], MOCK_PUBKEY);
+    assert_eq!(keygen_output["module_name"], MODULE_NAME);
+    assert_eq!(keygen_output["intel_report"], "report");
+    assert_eq!(enclave.keys(), vec![MOCK_PUBKEY.to_string()]);
+
+    // the generated key is listed afterwards
+    let output = dir.coral_ok(&["validator", "list-keys", "--enclave-url", &enclave.url()]);
+    assert!(output.contains(MOCK_PUBKEY), "unexpected output: {output}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_enclave_sign_voluntary_exit() {
+    let enclave = start_mock_enclave(vec![MOCK_PUBKEY.to_string()]).await;
+    let dir = TestDir::new("mock-sign-exit");
+    let enclave_url = enclave.url();
+    let output_file = dir.path("exit.json");
+    let output_file = output_file.to_str().unwrap();
+
+    dir.coral_ok(&sign_voluntary_exit_args(
+        &enclave_url,
+        MOCK_PUBKEY,
+        output_file,
+    ));
+    let exit = read_json(output_file);
+    assert_eq!(exit["message"]["epoch"], "100");
+    assert_eq!(exit["message"]["validator_index"], "42");
+    // 96 bytes, written as returned since coral-cli does not verify it
+    let signature = exit["signature"].as_str().unwrap().to_string();
+    assert_eq!(normalize_hex(&signature).len(), 192);
+
+    // the same request is answered with the same signature
+    dir.coral_ok(&sign_voluntary_exit_args(
+        &enclave_url,
+        MOCK_PUBKEY,
+        output_file,
+    ));
+    assert_eq!(read_json(output_file)["signature"], signature);
+
+    let unknown_pubkey = format!("0x{}", "ab".repeat(48));
+    let output = dir.coral(&sign_voluntary_exit_args(
+        &enclave_url,
+        &unknown_pubkey,
+        output_file,
+    ));
+    assert_failed(output, "Failed to sign_voluntary_exit_message");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_enclave_unhealthy() {
+    let enclave = start_mock_enclave(vec![MOCK_PUBKEY.to_string()]).await;
+    enclave.set_healthy(false);
+    let dir = TestDir::new("mock-unhealthy");
+    let enclave_url = enclave.url();
+    let output_file = dir.path("output.json");
+    let output_file = output_file.to_str().unwrap();
+
+    let output = dir.coral(&["validator", "list-keys", "--enclave-url", &enclave_url]);
+    assert_failed(output, "Health check failed");
+
+    let output = dir.coral(&[
+        "validator",
+        "keygen",
+        "--enclave-url",
+        &enclave_url,
+        "--guardian-pubkeys",
+        GUARDIAN_PUBKEY,
+        "--guardian-threshold",
+        "1",
+        "--module-name",
+        MODULE_NAME,
+        "--withdrawal-credentials",
+        &format!("0x01{}", "00".repeat(31)),
+        "--fork-version",
+        FORK_VERSION,
+        "--output-file",
+        output_file,
+    ]);
+    assert_failed(output, "Enclave health check failed");
+
+    let output = dir.coral(&sign_voluntary_exit_args(
+        &enclave_url,
+        MOCK_PUBKEY,
+        output_file,
+    ));
+    assert_failed(output, "Enclave health check failed");
+    assert!(!dir.path("output.json").exists());
+
+    // healthy again
+    enclave.set_healthy(true);
+    dir.coral_ok(&["validator", "list-keys", "--enclave-url", &enclave_url]);
+}
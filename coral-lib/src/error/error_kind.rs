@@ -4,7 +4,7 @@ use std::io;
 #[cfg(feature = "dev")]
 use ethers::signers::WalletError;
 
-use super::ServerErrorResponse;
+use super::{ServerErrorCode, ServerErrorResponse};
 
 #[derive(Clone, Debug)]
 pub enum AppErrorKind {
@@ -21,10 +21,11 @@ pub enum AppErrorKind {
     JsonDeError,
 
     ContractCallError,
+    TransactionError,
 
     HyperError,
     ReqwestError,
-    ServerError,
+    ServerError(ServerErrorCode),
     SqlError,
 
     EthersWalletError,
@@ -33,8 +34,8 @@ pub enum AppErrorKind {
 }
 
 impl From<ServerErrorResponse> for AppErrorKind {
-    fn from(_err: ServerErrorResponse) -> Self {
-        Self::ServerError
+    fn from(err: ServerErrorResponse) -> Self {
+        Self::ServerError(err.response.result.error_code)
     }
 }
 
@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize, Serializer};
 pub enum ServerErrorCode {
     // config errors
     ConfigError = 100_000,
+    ProfileNotFound,
+    ProfileChainIdMismatch,
 
     //
     EnclaveConnectionError = 400_100,
@@ -20,6 +22,8 @@ pub enum ServerErrorCode {
 
     RegisterDataInvalid,
 
+    EnclaveMockServerError,
+
     //
     ParseError = 500_100,
 
@@ -72,8 +76,37 @@ pub enum ServerErrorCode {
 
     BeaconSubmitVoluntaryExitError,
 
+    // transaction simulation
+    EvmCallError,
+    EvmEstimateGasError,
+    EvmTransactionAlreadyMined,
+
+    // deployed contract checks
+    EvmFetchCodeError,
+    EvmFetchStorageError,
+
+    // RPC transports
+    EvmConnectionError,
+    EvmRequestTimeout,
+    EvmFailoverExhausted,
+
+    // beacon node API
+    BeaconFetchGenesisError,
+    BeaconFetchForkError,
+    BeaconFetchFinalityError,
+
     // puffer errors
     PufferVaultInsufficientETH = 600_100,
+
+    // registration pre-flight checks
+    PufferModuleNotFound,
+    PufferModuleLimitReached,
+    PufferProtocolPaused,
+    PufferValidatorAlreadyRegistered,
+    PufferWithdrawalCredentialsMismatch,
+
+    // vault
+    PufferVaultDailyWithdrawalLimitExceeded,
 }
 
 impl ServerErrorCode {
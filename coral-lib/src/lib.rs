@@ -1,4 +1,7 @@
 pub mod error;
+#[cfg(feature = "dev")]
+pub mod local_db;
+pub mod profile;
 pub mod structs;
 pub mod utils;
 
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use axum::http::StatusCode;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS protocol_events (
+    block_number INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    tx_hash TEXT NOT NULL,
+    event TEXT NOT NULL,
+    module_name TEXT,
+    puffer_module_index INTEGER,
+    pub_key TEXT,
+    node TEXT,
+    amount TEXT,
+    PRIMARY KEY (tx_hash, log_index)
+);
+CREATE INDEX IF NOT EXISTS protocol_events_pub_key ON protocol_events (pub_key);
+CREATE INDEX IF NOT EXISTS protocol_events_node ON protocol_events (node);
+CREATE TABLE IF NOT EXISTS sync_progress (
+    name TEXT PRIMARY KEY,
+    block_number INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS delivered_events (
+    name TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    PRIMARY KEY (name, tx_hash, log_index)
+);
+";
+
+/// A PufferProtocol event relevant to one of our nodes.
+/// Addresses, hashes and public keys are lowercase 0x-prefixed hex,
+/// amounts are decimal strings in wei.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolEvent {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub tx_hash: String,
+    pub event: String,
+    pub module_name: Option<String>,
+    pub puffer_module_index: Option<u64>,
+    pub pub_key: Option<String>,
+    pub node: Option<String>,
+    pub amount: Option<String>,
+}
+
+/// SQLite cache of indexed events and sync progress
+pub struct LocalDb {
+    conn: Connection,
+}
+
+fn db_error(
+    error_code: ServerErrorCode,
+    error_msg: &str,
+    err: rusqlite::Error,
+) -> ServerErrorResponse {
+    tracing::error!("{error_msg}: {err}");
+    ServerErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        error_code,
+        format!("{error_msg}: {err}"),
+    )
+}
+
+fn insert_event(conn: &Connection, event: &ProtocolEvent) -> AppServerResult<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO protocol_events
+         (block_number, log_index, tx_hash, event, module_name, puffer_module_index, pub_key, node, amount)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            event.block_number,
+            event.log_index,
+            event.tx_hash,
+            event.event,
+            event.module_name,
+            event.puffer_module_index,
+            event.pub_key,
+            event.node,
+            event.amount,
+        ],
+    )
+    .map_err(|err| db_error(ServerErrorCode::LocalDbInsertError, "Failed to insert event", err))?;
+    Ok(())
+}
+
+impl LocalDb {
+    pub fn open(path: &Path) -> AppServerResult<Self> {
+        let conn = Connection::open(path).map_err(|err| {
+            let error_msg = format!("Failed to open database '{}'", path.display());
+            db_error(ServerErrorCode::LocalDbConnectionError, &error_msg, err)
+        })?;
+        conn.execute_batch(SCHEMA).map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbConnectionError,
+                "Failed to create database schema",
+                err,
+            )
+        })?;
+        Ok(Self { conn })
+    }
+
+    /// Last block fully processed by `name`, e.g. a sync of one contract
+    pub fn get_progress(&self, name: &str) -> AppServerResult<Option<u64>> {
+        self.conn
+            .query_row(
+                "SELECT block_number FROM sync_progress WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| {
+                db_error(
+                    ServerErrorCode::LocalDbFetchError,
+                    "Failed to fetch sync progress",
+                    err,
+                )
+            })
+    }
+
+    pub fn set_progress(&self, name: &str, block_number: u64) -> AppServerResult<()> {
+        self.conn
+            .execute(
+                "INSERT INTO sync_progress (name, block_number) VALUES (?1, ?2)
+                 ON CONFLICT (name) DO UPDATE SET block_number = excluded.block_number",
+                params![name, block_number],
+            )
+            .map_err(|err| {
+                db_error(
+                    ServerErrorCode::LocalDbUpdateError,
+                    "Failed to update sync progress",
+                    err,
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Store events and move the progress of `name` to `block_number` atomically,
+    /// so an interrupted sync resumes without gaps. Events already stored are ignored.
+    pub fn insert_events(
+        &mut self,
+        events: &[ProtocolEvent],
+        name: &str,
+        block_number: u64,
+    ) -> AppServerResult<()> {
+        let tx = self.conn.transaction().map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbInsertError,
+                "Failed to start transaction",
+                err,
+            )
+        })?;
+        for event in events {
+            insert_event(&tx, event)?;
+        }
+        tx.execute(
+            "INSERT INTO sync_progress (name, block_number) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET block_number = excluded.block_number",
+            params![name, block_number],
+        )
+        .map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbUpdateError,
+                "Failed to update sync progress",
+                err,
+            )
+        })?;
+        tx.commit().map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbInsertError,
+                "Failed to commit events",
+                err,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Whether `event` was already delivered by `name`, e.g. a watcher
+    pub fn is_delivered(&self, name: &str, event: &ProtocolEvent) -> AppServerResult<bool> {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM delivered_events WHERE name = ?1 AND tx_hash = ?2 AND log_index = ?3",
+                params![name, event.tx_hash, event.log_index],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|row| row.is_some())
+            .map_err(|err| {
+                db_error(
+                    ServerErrorCode::LocalDbFetchError,
+                    "Failed to fetch delivered event",
+                    err,
+                )
+            })
+    }
+
+    /// Store `event` and mark it as delivered by `name` atomically,
+    /// so it is not delivered again after a restart
+    pub fn insert_delivered_event(
+        &mut self,
+        event: &ProtocolEvent,
+        name: &str,
+    ) -> AppServerResult<()> {
+        let tx = self.conn.transaction().map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbInsertError,
+                "Failed to start transaction",
+                err,
+            )
+        })?;
+        insert_event(&tx, event)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO delivered_events (name, tx_hash, log_index) VALUES (?1, ?2, ?3)",
+            params![name, event.tx_hash, event.log_index],
+        )
+        .map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbInsertError,
+                "Failed to mark event as delivered",
+                err,
+            )
+        })?;
+        tx.commit().map_err(|err| {
+            db_error(
+                ServerErrorCode::LocalDbInsertError,
+                "Failed to commit event",
+                err,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Public keys registered by our nodes, mapped to the registering node
+    pub fn get_registered_pubkeys(&self) -> AppServerResult<HashMap<String, String>> {
+        let fetch_error = |err| {
+            db_error(
+                ServerErrorCode::LocalDbFetchError,
+                "Failed to fetch registered public keys",
+                err,
+            )
+        };
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT pub_key, node FROM protocol_events
+                 WHERE event = 'ValidatorKeyRegistered' AND pub_key IS NOT NULL AND node IS NOT NULL",
+            )
+            .map_err(fetch_error)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(fetch_error)?;
+        rows.collect::<Result<HashMap<String, String>, _>>()
+            .map_err(fetch_error)
+    }
+
+    /// Stored events in chain order, optionally only for one public key and/or node
+    pub fn get_events(
+        &self,
+        pub_key: Option<&str>,
+        node: Option<&str>,
+    ) -> AppServerResult<Vec<ProtocolEvent>> {
+        let fetch_error = |err| {
+            db_error(
+                ServerErrorCode::LocalDbFetchError,
+                "Failed to fetch events",
+                err,
+            )
+        };
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT block_number, log_index, tx_hash, event, module_name, puffer_module_index, pub_key, node, amount
+                 FROM protocol_events
+                 WHERE (?1 IS NULL OR pub_key = ?1) AND (?2 IS NULL OR node = ?2)
+                 ORDER BY block_number, log_index",
+            )
+            .map_err(fetch_error)?;
+        let rows = stmt
+            .query_map(params![pub_key, node], |row| {
+                Ok(ProtocolEvent {
+                    block_number: row.get(0)?,
+                    log_index: row.get(1)?,
+                    tx_hash: row.get(2)?,
+                    event: row.get(3)?,
+                    module_name: row.get(4)?,
+                    puffer_module_index: row.get(5)?,
+                    pub_key: row.get(6)?,
+                    node: row.get(7)?,
+                    amount: row.get(8)?,
+                })
+            })
+            .map_err(fetch_error)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(fetch_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(block_number: u64, log_index: u64, event: &str, pub_key: &str) -> ProtocolEvent {
+        ProtocolEvent {
+            block_number,
+            log_index,
+            tx_hash: format!("0x{block_number:064x}"),
+            event: event.to_string(),
+            module_name: Some("PUFFER_MODULE_0".to_string()),
+            puffer_module_index: Some(0),
+            pub_key: Some(pub_key.to_string()),
+            node: Some("0x2a3f".to_string()),
+            amount: None,
+        }
+    }
+
+    fn open() -> LocalDb {
+        LocalDb::open(Path::new(":memory:")).unwrap()
+    }
+
+    #[test]
+    fn stores_events_with_progress() {
+        let mut db = open();
+        assert_eq!(db.get_progress("index").unwrap(), None);
+
+        let events = vec![
+            event(20, 0, "SuccessfullyProvisioned", "0xaa"),
+            event(10, 3, "ValidatorKeyRegistered", "0xaa"),
+            event(10, 4, "ValidatorKeyRegistered", "0xbb"),
+        ];
+        db.insert_events(&events, "index", 30).unwrap();
+        // stored again with the next chunk
+        db.insert_events(&events[..1], "index", 40).unwrap();
+        assert_eq!(db.get_progress("index").unwrap(), Some(40));
+
+        let stored = db.get_events(None, None).unwrap();
+        assert_eq!(
+            stored,
+            vec![events[1].clone(), events[2].clone(), events[0].clone()]
+        );
+        let stored = db.get_events(Some("0xaa"), None).unwrap();
+        assert_eq!(stored, vec![events[1].clone(), events[0].clone()]);
+        assert!(db.get_events(None, Some("0x8c1d")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn returns_registered_pubkeys() {
+        let mut db = open();
+        let events = vec![
+            event(10, 0, "ValidatorKeyRegistered", "0xaa"),
+            event(20, 0, "SuccessfullyProvisioned", "0xbb"),
+        ];
+        db.insert_events(&events, "index", 20).unwrap();
+
+        let registered = db.get_registered_pubkeys().unwrap();
+        assert_eq!(
+            registered,
+            HashMap::from([("0xaa".to_string(), "0x2a3f".to_string())])
+        );
+    }
+
+    #[test]
+    fn tracks_delivery_per_name() {
+        let mut db = open();
+        let event = event(10, 0, "ValidatorKeyRegistered", "0xaa");
+        assert!(!db.is_delivered("watch", &event).unwrap());
+
+        db.insert_delivered_event(&event, "watch").unwrap();
+        db.insert_delivered_event(&event, "watch").unwrap();
+        assert!(db.is_delivered("watch", &event).unwrap());
+        assert!(!db.is_delivered("other-watch", &event).unwrap());
+        assert_eq!(db.get_events(None, None).unwrap(), vec![event]);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::structs::eth_types::ForkVersionInfo;
+
+/// Contract addresses of a network
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ContractAddresses {
+    pub puffer_protocol: Option<String>,
+    pub validator_ticket: Option<String>,
+    pub puffer_oracle: Option<String>,
+    pub guardian_module: Option<String>,
+    pub puffer_vault: Option<String>,
+    pub puffer_module_manager: Option<String>,
+}
+
+/// Settings of one network, selected with `--profile`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub chain_id: u64,
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    pub beacon_url: Option<String>,
+    pub enclave_url: Option<String>,
+    #[serde(default)]
+    pub contracts: ContractAddresses,
+    pub fork: Option<ForkVersionInfo>,
+    /// Timeout of each RPC request, in seconds
+    pub rpc_timeout: Option<u64>,
+    /// Rounds over all RPC endpoints after the first one
+    pub rpc_max_retries: Option<u32>,
+}
+
+impl Profile {
+    /// All RPC URLs, comma-separated, to fail over between them
+    pub fn rpc_url(&self) -> Option<String> {
+        if self.rpc_urls.is_empty() {
+            return None;
+        }
+        Some(self.rpc_urls.join(","))
+    }
+}
+
+/// Profiles file: profile name -> profile
+pub type Profiles = BTreeMap<String, Profile>;
+
+/// `$CORAL_PROFILES`, or `~/.coral/profiles.json`
+pub fn default_profiles_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("CORAL_PROFILES") {
+        return Some(PathBuf::from(path));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".coral").join("profiles.json"))
+}
+
+pub fn read_profiles(path: &Path) -> AppServerResult<Profiles> {
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        let error_msg = format!("Failed to read profiles file '{}'", path.display());
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ConfigError,
+            format!("{error_msg}: {err}"),
+        )
+    })?;
+    serde_json::from_str(&content).map_err(|err| {
+        let error_msg = format!("Invalid profiles file '{}'", path.display());
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ConfigError,
+            format!("{error_msg}: {err}"),
+        )
+    })
+}
+
+pub fn load_profile(path: &Path, name: &str) -> AppServerResult<Profile> {
+    let mut profiles = read_profiles(path)?;
+    profiles.remove(name).ok_or_else(|| {
+        let error_msg = format!("Profile '{name}' not found in '{}'", path.display());
+        tracing::error!("{error_msg}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ProfileNotFound,
+            error_msg,
+        )
+    })
+}
+
+/// Fail if the RPC is connected to another network than the profile
+pub fn check_chain_id(profile: &Profile, chain_id: u64) -> AppServerResult<()> {
+    if profile.chain_id != chain_id {
+        let error_msg = format!(
+            "Chain ID mismatch: profile expects {} but RPC returned {chain_id}",
+            profile.chain_id
+        );
+        tracing::error!("{error_msg}");
+        return Err(ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ProfileChainIdMismatch,
+            error_msg,
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::add_0x_prefix;
+
+/// The beacon API encodes integers as decimal strings
+mod quoted_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// State to query: `head`, `finalized`, `justified`, `genesis`, a slot or a `0x`-prefixed state root
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateId {
+    Head,
+    Finalized,
+    Justified,
+    Genesis,
+    Slot(u64),
+    Root(String),
+}
+
+impl fmt::Display for StateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Head => write!(f, "head"),
+            Self::Finalized => write!(f, "finalized"),
+            Self::Justified => write!(f, "justified"),
+            Self::Genesis => write!(f, "genesis"),
+            Self::Slot(slot) => write!(f, "{slot}"),
+            Self::Root(root) => write!(f, "{}", add_0x_prefix(root)),
+        }
+    }
+}
+
+/// Validator index, or `0x`-prefixed BLS public key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidatorId {
+    Index(u64),
+    Pubkey(String),
+}
+
+impl fmt::Display for ValidatorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Pubkey(pubkey) => write!(f, "{}", add_0x_prefix(pubkey)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Genesis {
+    #[serde(with = "quoted_u64")]
+    pub genesis_time: u64,
+    pub genesis_validators_root: String,
+    pub genesis_fork_version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BeaconFork {
+    pub previous_version: String,
+    pub current_version: String,
+    #[serde(with = "quoted_u64")]
+    pub epoch: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ActiveSlashed,
+    ExitedUnslashed,
+    ExitedSlashed,
+    WithdrawalPossible,
+    WithdrawalDone,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Validator {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    #[serde(with = "quoted_u64")]
+    pub effective_balance: u64,
+    pub slashed: bool,
+    #[serde(with = "quoted_u64")]
+    pub activation_eligibility_epoch: u64,
+    #[serde(with = "quoted_u64")]
+    pub activation_epoch: u64,
+    #[serde(with = "quoted_u64")]
+    pub exit_epoch: u64,
+    #[serde(with = "quoted_u64")]
+    pub withdrawable_epoch: u64,
+}
+
+/// A validator in a beacon state, balances in gwei
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidatorData {
+    #[serde(with = "quoted_u64")]
+    pub index: u64,
+    #[serde(with = "quoted_u64")]
+    pub balance: u64,
+    pub status: ValidatorStatus,
+    pub validator: Validator,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidatorBalance {
+    #[serde(with = "quoted_u64")]
+    pub index: u64,
+    #[serde(with = "quoted_u64")]
+    pub balance: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Checkpoint {
+    #[serde(with = "quoted_u64")]
+    pub epoch: u64,
+    pub root: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FinalityCheckpoints {
+    pub previous_justified: Checkpoint,
+    pub current_justified: Checkpoint,
+    pub finalized: Checkpoint,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoluntaryExit {
+    #[serde(with = "quoted_u64")]
+    pub epoch: u64,
+    #[serde(with = "quoted_u64")]
+    pub validator_index: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedVoluntaryExit {
+    pub message: VoluntaryExit,
+    pub signature: String,
+}
@@ -1,3 +1,15 @@
+use serde::{Deserialize, Serialize};
+
 pub type ForkVersion = [u8; 4];
 pub type Root = [u8; 32];
 pub type WithdrawalCredentials = [u8; 32];
+
+/// Fork constants of a network, as hex strings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForkVersionInfo {
+    pub current_version: String,
+    pub previous_version: String,
+    pub genesis_version: String,
+    pub genesis_validators_root: String,
+    pub epoch: u64,
+}
@@ -1 +1,5 @@
+pub mod beacon_types;
 pub mod eth_types;
+pub mod module_name;
+
+pub use self::module_name::ModuleName;
@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+
+/// A PufferProtocol module name, stored on chain as a zero-padded bytes32.
+///
+/// Parses from either:
+///  - hex: `0x`-prefixed of at most 32 bytes, or unprefixed bytes32
+///  - ASCII: printable characters, at most 32 bytes, e.g. `PUFFER_MODULE_0`
+///
+/// A shorter unprefixed string that is valid hex (e.g. `CAFE`) is rejected as ambiguous.
+/// Displays as ASCII when it would not be read back as hex, else as hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModuleName([u8; 32]);
+
+fn parse_error(error_msg: String) -> ServerErrorResponse {
+    tracing::error!("{error_msg}");
+    ServerErrorResponse::new(
+        StatusCode::BAD_REQUEST,
+        ServerErrorCode::ParseError,
+        error_msg,
+    )
+}
+
+fn is_hex(s: &str) -> bool {
+    hex::decode(s).is_ok()
+}
+
+impl ModuleName {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn parse(module_name: &str) -> AppServerResult<Self> {
+        if let Some(module_name_hex) = module_name.strip_prefix("0x") {
+            return Self::from_hex(module_name_hex);
+        }
+        if !module_name.is_empty() && is_hex(module_name) {
+            if module_name.len() == 64 {
+                return Self::from_hex(module_name);
+            }
+            return Err(parse_error(format!(
+                "Ambiguous module name '{module_name}', it is both hex and ASCII: \
+                 pass '0x{module_name}' for hex or '0x{}' for ASCII",
+                hex::encode(module_name)
+            )));
+        }
+        Self::from_ascii(module_name)
+    }
+
+    fn from_hex(module_name_hex: &str) -> AppServerResult<Self> {
+        let bytes = hex::decode(module_name_hex).map_err(|err| {
+            parse_error(format!(
+                "Failed to decode module name '0x{module_name_hex}': {err}"
+            ))
+        })?;
+        Self::from_slice(&bytes)
+    }
+
+    fn from_ascii(module_name: &str) -> AppServerResult<Self> {
+        if module_name.is_empty() || !module_name.chars().all(|c| c.is_ascii_graphic()) {
+            return Err(parse_error(format!(
+                "Invalid module name '{module_name}', expected printable ASCII or hex"
+            )));
+        }
+        Self::from_slice(module_name.as_bytes())
+    }
+
+    fn from_slice(bytes: &[u8]) -> AppServerResult<Self> {
+        if bytes.len() > 32 {
+            return Err(parse_error(format!(
+                "Module name is {} bytes, at most 32 are allowed",
+                bytes.len()
+            )));
+        }
+        let mut module_name = [0u8; 32];
+        module_name[..bytes.len()].copy_from_slice(bytes);
+        Ok(Self(module_name))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// `0x`-prefixed bytes32 hex
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+
+    /// The name without zero padding, if it is printable ASCII
+    /// that would not be read back as hex
+    pub fn to_ascii(&self) -> Option<String> {
+        let end = self.0.iter().rposition(|byte| *byte != 0)? + 1;
+        let name = &self.0[..end];
+        if !name.iter().all(|byte| byte.is_ascii_graphic()) {
+            return None;
+        }
+        let name = String::from_utf8_lossy(name).into_owned();
+        if is_hex(&name) || name.starts_with("0x") {
+            return None;
+        }
+        Some(name)
+    }
+}
+
+impl From<[u8; 32]> for ModuleName {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ModuleName> for [u8; 32] {
+    fn from(module_name: ModuleName) -> Self {
+        module_name.0
+    }
+}
+
+impl FromStr for ModuleName {
+    type Err = ServerErrorResponse;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for ModuleName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_ascii() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "{}", self.to_hex()),
+        }
+    }
+}
+
+impl Serialize for ModuleName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ModuleName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let module_name = String::deserialize(deserializer)?;
+        Self::parse(&module_name).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded(bytes: &[u8]) -> [u8; 32] {
+        let mut padded = [0u8; 32];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        padded
+    }
+
+    #[test]
+    fn parses_ascii() {
+        let module_name = ModuleName::parse("PUFFER_MODULE_0").unwrap();
+        assert_eq!(module_name.to_bytes(), padded(b"PUFFER_MODULE_0"));
+        assert_eq!(module_name.to_string(), "PUFFER_MODULE_0");
+    }
+
+    #[test]
+    fn parses_prefixed_hex() {
+        let module_name = ModuleName::parse("0x5055464645525f4d4f44554c455f30").unwrap();
+        assert_eq!(module_name.to_bytes(), padded(b"PUFFER_MODULE_0"));
+    }
+
+    #[test]
+    fn rejects_ambiguous_hex() {
+        let err = ModuleName::parse("CAFE").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Ambiguous module name 'CAFE', it is both hex and ASCII: \
+             pass '0xCAFE' for hex or '0x43414645' for ASCII"
+        );
+        assert!(ModuleName::parse("5055464645525f4d4f44554c455f30").is_err());
+
+        let module_name = ModuleName::parse("0xCAFE").unwrap();
+        assert_eq!(module_name.to_bytes(), padded(&[0xca, 0xfe]));
+        assert_eq!(module_name.to_string(), format!("0xcafe{}", "0".repeat(60)));
+
+        let module_name = ModuleName::parse("0x43414645").unwrap();
+        assert_eq!(module_name.to_bytes(), padded(b"CAFE"));
+        // printed as hex, so it reads back as the same name
+        assert_eq!(module_name.to_string(), module_name.to_hex());
+        assert_eq!(
+            ModuleName::parse(&module_name.to_string()).unwrap(),
+            module_name
+        );
+    }
+
+    #[test]
+    fn parses_bytes32_hex() {
+        let bytes32_hex = hex::encode(padded(b"PUFFER_MODULE_0"));
+        assert_eq!(bytes32_hex.len(), 64);
+        let module_name = ModuleName::parse(&bytes32_hex).unwrap();
+        assert_eq!(module_name.to_bytes(), padded(b"PUFFER_MODULE_0"));
+        assert_eq!(
+            ModuleName::parse(&module_name.to_hex()).unwrap(),
+            module_name
+        );
+    }
+
+    #[test]
+    fn rejects_too_long() {
+        assert!(ModuleName::parse(&"M".repeat(32)).is_ok());
+        assert!(ModuleName::parse(&"M".repeat(33)).is_err());
+        assert!(ModuleName::parse(&format!("0x{}", "ab".repeat(33))).is_err());
+    }
+
+    #[test]
+    fn rejects_non_printable() {
+        assert!(ModuleName::parse("").is_err());
+        assert!(ModuleName::parse("PUFFER MODULE").is_err());
+        assert!(ModuleName::parse("PUFFER\tMODULE").is_err());
+        assert!(ModuleName::parse("MODULE_\u{e9}").is_err());
+    }
+}
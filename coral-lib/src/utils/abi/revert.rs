@@ -0,0 +1,296 @@
+use ethers::abi::{self, Abi};
+
+/// Selector of the standard solidity `Error(string)` revert
+const REVERT_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the standard solidity `Panic(uint256)` revert
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+#[derive(Clone, Debug)]
+pub struct DecodedRevert {
+    pub contract: Option<String>,
+    pub name: String,
+    pub args: Vec<abi::Token>,
+}
+
+/// Explanations of errors Puffer contracts are known to revert with, by contract and error name.
+/// The same error name can mean different things in different contracts,
+/// e.g. `Unauthorized` in the GuardianModule is about guardian signatures.
+/// Standard reverts have no contract.
+const REASONS: &[(Option<&str>, &str, &str)] = &[
+    (
+        Some("PufferProtocol"),
+        "ValidatorLimitForModuleReached",
+        "The module has reached its validator limit",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidETHAmount",
+        "The ETH sent does not cover the bond and validator tickets",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidVTAmount",
+        "Not enough validator tickets for the minimum amount of days",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidBLSPubKey",
+        "The BLS public key has an invalid length",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidBLSPublicKeySet",
+        "The BLS public key set does not match the guardian set",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidBLSPrivateKeyShares",
+        "The number of encrypted key shares does not match the number of guardians",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidDepositRootHash",
+        "The deposit data root is invalid",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidValidatorState",
+        "The validator is not in the expected state",
+    ),
+    (
+        Some("PufferProtocol"),
+        "InvalidAddress",
+        "The module does not exist",
+    ),
+    (
+        Some("PufferProtocol"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function, or the protocol is paused",
+    ),
+    (
+        Some("GuardianModule"),
+        "InvalidAddress",
+        "An address is invalid",
+    ),
+    (
+        Some("GuardianModule"),
+        "InvalidRAVE",
+        "The guardian enclave RAVE evidence was rejected",
+    ),
+    (
+        Some("GuardianModule"),
+        "InvalidECDSAPubKey",
+        "The guardian enclave public key is invalid",
+    ),
+    (
+        Some("GuardianModule"),
+        "InvalidThreshold",
+        "The guardian threshold is invalid",
+    ),
+    (
+        Some("GuardianModule"),
+        "Unauthorized",
+        "Guardian signatures are invalid or below the threshold",
+    ),
+    (
+        Some("GuardianModule"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function",
+    ),
+    (
+        Some("PufferModule"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function",
+    ),
+    (
+        Some("PufferOracle"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function",
+    ),
+    (
+        Some("PufferVault"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function",
+    ),
+    (
+        Some("PufferVault"),
+        "ERC20InsufficientBalance",
+        "The pufETH balance is too low",
+    ),
+    (
+        Some("PufferVault"),
+        "ERC20InsufficientAllowance",
+        "The pufETH allowance is too low",
+    ),
+    (
+        Some("ValidatorTicket"),
+        "AccessManagedUnauthorized",
+        "The caller is not allowed to call this function",
+    ),
+    (
+        Some("ValidatorTicket"),
+        "ERC20InsufficientBalance",
+        "The validator ticket balance is too low",
+    ),
+    (
+        Some("ValidatorTicket"),
+        "ERC20InsufficientAllowance",
+        "The validator ticket allowance is too low",
+    ),
+    (
+        None,
+        "Panic",
+        "The contract panicked (e.g. arithmetic overflow)",
+    ),
+];
+
+impl DecodedRevert {
+    /// Human-readable explanation for errors Puffer contracts are known to revert with
+    pub fn reason(&self) -> Option<&'static str> {
+        REASONS
+            .iter()
+            .find(|(contract, name, _)| *contract == self.contract.as_deref() && *name == self.name)
+            .map(|(_, _, reason)| *reason)
+    }
+}
+
+impl std::fmt::Display for DecodedRevert {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(contract) = self.contract.as_ref() {
+            write!(f, "{contract}.")?;
+        }
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+        write!(f, "{}({})", self.name, args.join(", "))?;
+        if let Some(reason) = self.reason() {
+            write!(f, ": {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode revert data returned by `eth_call` / `eth_estimateGas`
+/// against the custom errors of the given contract ABIs
+pub fn decode_revert_data(data: &[u8], abis: &[(&str, &Abi)]) -> Option<DecodedRevert> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, params) = data.split_at(4);
+
+    if selector == REVERT_STRING_SELECTOR {
+        let args = abi::decode(&[abi::ParamType::String], params).ok()?;
+        return Some(DecodedRevert {
+            contract: None,
+            name: "Error".to_string(),
+            args,
+        });
+    }
+    if selector == PANIC_SELECTOR {
+        let args = abi::decode(&[abi::ParamType::Uint(256)], params).ok()?;
+        return Some(DecodedRevert {
+            contract: None,
+            name: "Panic".to_string(),
+            args,
+        });
+    }
+
+    for (contract, abi) in abis {
+        for error in abi.errors() {
+            if error.signature().as_bytes()[..4] != *selector {
+                continue;
+            }
+            if let Ok(args) = error.decode(params) {
+                return Some(DecodedRevert {
+                    contract: Some(contract.to_string()),
+                    name: error.name.clone(),
+                    args,
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{parse_abi, Token};
+    use ethers::types::{Address, U256};
+
+    use super::*;
+
+    fn selector(abi: &Abi, name: &str) -> Vec<u8> {
+        abi.error(name).unwrap().signature().as_bytes()[..4].to_vec()
+    }
+
+    fn revert_data(selector: &[u8], args: &[Token]) -> Vec<u8> {
+        [selector, &abi::encode(args)].concat()
+    }
+
+    #[test]
+    fn decodes_custom_error_with_args() {
+        let protocol = parse_abi(&["error AccessManagedUnauthorized(address caller)"]).unwrap();
+        let caller = Address::repeat_byte(0x11);
+        let data = revert_data(
+            &selector(&protocol, "AccessManagedUnauthorized"),
+            &[Token::Address(caller)],
+        );
+
+        let decoded = decode_revert_data(&data, &[("PufferProtocol", &protocol)]).unwrap();
+        assert_eq!(decoded.contract.as_deref(), Some("PufferProtocol"));
+        assert_eq!(decoded.name, "AccessManagedUnauthorized");
+        assert_eq!(decoded.args, vec![Token::Address(caller)]);
+        assert_eq!(
+            decoded.reason(),
+            Some("The caller is not allowed to call this function, or the protocol is paused")
+        );
+    }
+
+    #[test]
+    fn reason_depends_on_contract() {
+        let abi = parse_abi(&["error InvalidAddress()"]).unwrap();
+        let data = selector(&abi, "InvalidAddress");
+
+        let decoded = decode_revert_data(&data, &[("PufferProtocol", &abi)]).unwrap();
+        assert_eq!(decoded.reason(), Some("The module does not exist"));
+        let decoded = decode_revert_data(&data, &[("GuardianModule", &abi)]).unwrap();
+        assert_eq!(decoded.reason(), Some("An address is invalid"));
+        let decoded = decode_revert_data(&data, &[("RestakingOperator", &abi)]).unwrap();
+        assert_eq!(decoded.reason(), None);
+    }
+
+    #[test]
+    fn decodes_error_string() {
+        let data = revert_data(
+            &REVERT_STRING_SELECTOR,
+            &[Token::String("not enough ETH".to_string())],
+        );
+
+        let decoded = decode_revert_data(&data, &[]).unwrap();
+        assert_eq!(decoded.contract, None);
+        assert_eq!(decoded.name, "Error");
+        assert_eq!(decoded.reason(), None);
+        assert_eq!(decoded.to_string(), "Error(not enough ETH)");
+    }
+
+    #[test]
+    fn decodes_panic() {
+        // arithmetic overflow
+        let data = revert_data(&PANIC_SELECTOR, &[Token::Uint(U256::from(0x11))]);
+
+        let decoded = decode_revert_data(&data, &[]).unwrap();
+        assert_eq!(decoded.name, "Panic");
+        assert_eq!(decoded.args, vec![Token::Uint(U256::from(0x11))]);
+        assert_eq!(
+            decoded.to_string(),
+            "Panic(11): The contract panicked (e.g. arithmetic overflow)"
+        );
+    }
+
+    #[test]
+    fn unknown_selector_is_not_decoded() {
+        let protocol = parse_abi(&["error ValidatorLimitForModuleReached()"]).unwrap();
+        let data = revert_data(&[0xde, 0xad, 0xbe, 0xef], &[Token::Uint(U256::one())]);
+
+        assert!(decode_revert_data(&data, &[("PufferProtocol", &protocol)]).is_none());
+        assert!(decode_revert_data(&[0xde, 0xad], &[]).is_none());
+    }
+}
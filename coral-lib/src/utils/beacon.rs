@@ -0,0 +1,484 @@
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::structs::beacon_types::{
+    BeaconFork, FinalityCheckpoints, Genesis, SignedVoluntaryExit, StateId, ValidatorBalance,
+    ValidatorData, ValidatorId,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Validator ids per request, to keep URLs under the length limit of most nodes
+const VALIDATOR_IDS_PER_REQUEST: usize = 64;
+
+/// `{"data": ...}` envelope of beacon API responses
+#[derive(Debug, Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// Error body of the beacon API
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+fn beacon_error(error_code: ServerErrorCode, error_msg: String) -> ServerErrorResponse {
+    tracing::error!("{error_msg}");
+    ServerErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, error_code, error_msg)
+}
+
+/// Error codes of a request: sending it or a status other than success, and reading the body
+#[derive(Clone, Copy, Debug)]
+struct ErrorCodes {
+    fetch: ServerErrorCode,
+    parse: ServerErrorCode,
+}
+
+const GENESIS_ERRORS: ErrorCodes = ErrorCodes {
+    fetch: ServerErrorCode::BeaconFetchGenesisError,
+    parse: ServerErrorCode::HttpParseBodyError,
+};
+
+const FORK_ERRORS: ErrorCodes = ErrorCodes {
+    fetch: ServerErrorCode::BeaconFetchForkError,
+    parse: ServerErrorCode::HttpParseBodyError,
+};
+
+const FINALITY_ERRORS: ErrorCodes = ErrorCodes {
+    fetch: ServerErrorCode::BeaconFetchFinalityError,
+    parse: ServerErrorCode::HttpParseBodyError,
+};
+
+const VALIDATOR_ERRORS: ErrorCodes = ErrorCodes {
+    fetch: ServerErrorCode::BeaconFetchValidatorError,
+    parse: ServerErrorCode::BeaconParseValidatorErrro,
+};
+
+fn join_ids(ids: &[ValidatorId]) -> String {
+    ids.iter()
+        .map(ValidatorId::to_string)
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Client of the beacon node API, `/eth/v1/...`
+#[derive(Clone, Debug)]
+pub struct BeaconClient {
+    http_client: reqwest::Client,
+    beacon_url: Url,
+}
+
+impl BeaconClient {
+    pub fn new(beacon_url: &str) -> AppServerResult<Self> {
+        let mut beacon_url = Url::parse(beacon_url).map_err(|err| {
+            let error_msg = "Invalid beacon URL";
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::HttpUrlError,
+                format!("{error_msg}: {err}"),
+            )
+        })?;
+        // API paths are joined to the URL, keep a path prefix such as `/beacon`
+        if !beacon_url.path().ends_with('/') {
+            let path = format!("{}/", beacon_url.path());
+            beacon_url.set_path(&path);
+        }
+        let http_client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|err| {
+                let error_msg = "Failed to create beacon client";
+                tracing::error!("{error_msg}: {err}");
+                ServerErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ServerErrorCode::HttpRequestError,
+                    format!("{error_msg}: {err}"),
+                )
+            })?;
+        Ok(Self {
+            http_client,
+            beacon_url,
+        })
+    }
+
+    fn url(&self, path: &str) -> AppServerResult<Url> {
+        self.beacon_url.join(path).map_err(|err| {
+            let error_msg = format!("Invalid beacon API path '{path}'");
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::HttpUrlError,
+                format!("{error_msg}: {err}"),
+            )
+        })
+    }
+
+    /// `GET` the `data` of a response, `None` if the node answers 404
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        error_codes: ErrorCodes,
+    ) -> AppServerResult<Option<T>> {
+        let url = self.url(path)?;
+        let response = self
+            .http_client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|err| beacon_error(error_codes.fetch, format!("GET {path} failed: {err}")))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let message = response
+                .json::<ErrorResponse>()
+                .await
+                .map(|body| body.message)
+                .unwrap_or_default();
+            return Err(beacon_error(
+                error_codes.fetch,
+                format!("GET {path} failed with {status}: {message}"),
+            ));
+        }
+
+        let body: DataResponse<T> = response.json().await.map_err(|err| {
+            beacon_error(
+                error_codes.parse,
+                format!("Failed to parse GET {path} response: {err}"),
+            )
+        })?;
+        Ok(Some(body.data))
+    }
+
+    /// `get`, failing if the node answers 404
+    async fn get_required<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        error_codes: ErrorCodes,
+    ) -> AppServerResult<T> {
+        self.get(path, query, error_codes)
+            .await?
+            .ok_or_else(|| beacon_error(error_codes.fetch, format!("GET {path} failed: not found")))
+    }
+
+    pub async fn genesis(&self) -> AppServerResult<Genesis> {
+        self.get_required("eth/v1/beacon/genesis", &[], GENESIS_ERRORS)
+            .await
+    }
+
+    pub async fn fork_schedule(&self) -> AppServerResult<Vec<BeaconFork>> {
+        self.get_required("eth/v1/config/fork_schedule", &[], FORK_ERRORS)
+            .await
+    }
+
+    pub async fn state_fork(&self, state_id: &StateId) -> AppServerResult<BeaconFork> {
+        let path = format!("eth/v1/beacon/states/{state_id}/fork");
+        self.get_required(&path, &[], FORK_ERRORS).await
+    }
+
+    pub async fn finality_checkpoints(
+        &self,
+        state_id: &StateId,
+    ) -> AppServerResult<FinalityCheckpoints> {
+        let path = format!("eth/v1/beacon/states/{state_id}/finality_checkpoints");
+        self.get_required(&path, &[], FINALITY_ERRORS).await
+    }
+
+    /// `None` if the validator is not in the state, e.g. its deposit is not processed yet
+    pub async fn validator(
+        &self,
+        state_id: &StateId,
+        validator_id: &ValidatorId,
+    ) -> AppServerResult<Option<ValidatorData>> {
+        let path = format!("eth/v1/beacon/states/{state_id}/validators/{validator_id}");
+        self.get(&path, &[], VALIDATOR_ERRORS).await
+    }
+
+    /// Validators by index or public key, validators not in the state are left out
+    pub async fn validators(
+        &self,
+        state_id: &StateId,
+        validator_ids: &[ValidatorId],
+    ) -> AppServerResult<Vec<ValidatorData>> {
+        let path = format!("eth/v1/beacon/states/{state_id}/validators");
+        let mut validators = Vec::with_capacity(validator_ids.len());
+        for ids in validator_ids.chunks(VALIDATOR_IDS_PER_REQUEST) {
+            let query = [("id", join_ids(ids))];
+            validators.extend(
+                self.get_required::<Vec<ValidatorData>>(&path, &query, VALIDATOR_ERRORS)
+                    .await?,
+            );
+        }
+        Ok(validators)
+    }
+
+    /// Balances in gwei by index or public key, validators not in the state are left out
+    pub async fn validator_balances(
+        &self,
+        state_id: &StateId,
+        validator_ids: &[ValidatorId],
+    ) -> AppServerResult<Vec<ValidatorBalance>> {
+        let path = format!("eth/v1/beacon/states/{state_id}/validator_balances");
+        let mut balances = Vec::with_capacity(validator_ids.len());
+        for ids in validator_ids.chunks(VALIDATOR_IDS_PER_REQUEST) {
+            let query = [("id", join_ids(ids))];
+            balances.extend(
+                self.get_required::<Vec<ValidatorBalance>>(&path, &query, VALIDATOR_ERRORS)
+                    .await?,
+            );
+        }
+        Ok(balances)
+    }
+
+    /// Voluntary exits known to the node and not yet included in a block
+    pub async fn pool_voluntary_exits(&self) -> AppServerResult<Vec<SignedVoluntaryExit>> {
+        let error_codes = ErrorCodes {
+            fetch: ServerErrorCode::BeaconFetchBlockError,
+            parse: ServerErrorCode::BeaconParseBlocKError,
+        };
+        self.get_required("eth/v1/beacon/pool/voluntary_exits", &[], error_codes)
+            .await
+    }
+
+    /// Submit a signed voluntary exit to the node's pool, to be broadcast
+    pub async fn submit_voluntary_exit(
+        &self,
+        voluntary_exit: &SignedVoluntaryExit,
+    ) -> AppServerResult<()> {
+        let path = "eth/v1/beacon/pool/voluntary_exits";
+        let url = self.url(path)?;
+        let response = self
+            .http_client
+            .post(url)
+            .json(voluntary_exit)
+            .send()
+            .await
+            .map_err(|err| {
+                beacon_error(
+                    ServerErrorCode::BeaconSubmitVoluntaryExitError,
+                    format!("POST {path} failed: {err}"),
+                )
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<ErrorResponse>()
+                .await
+                .map(|body| body.message)
+                .unwrap_or_default();
+            return Err(beacon_error(
+                ServerErrorCode::BeaconSubmitVoluntaryExitError,
+                format!(
+                    "Voluntary exit of validator {} rejected with {status}: {message}",
+                    voluntary_exit.message.validator_index
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::{Path, Query, State};
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::structs::beacon_types::ValidatorStatus;
+
+    /// `id` query of every validators request
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    fn validator_json(index: u64) -> Value {
+        json!({
+            "index": index.to_string(),
+            "balance": "32000000000",
+            "status": "active_ongoing",
+            "validator": {
+                "pubkey": format!("0x{index:096x}"),
+                "withdrawal_credentials": format!("0x01{:062x}", 0),
+                "effective_balance": "32000000000",
+                "slashed": false,
+                "activation_eligibility_epoch": "0",
+                "activation_epoch": "0",
+                "exit_epoch": "18446744073709551615",
+                "withdrawable_epoch": "18446744073709551615"
+            }
+        })
+    }
+
+    async fn genesis() -> Json<Value> {
+        Json(json!({
+            "data": {
+                "genesis_time": "1695902400",
+                "genesis_validators_root": "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1",
+                "genesis_fork_version": "0x01017000"
+            }
+        }))
+    }
+
+    async fn finality_checkpoints() -> impl IntoResponse {
+        (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "code": 500, "message": "State not available" })),
+        )
+    }
+
+    async fn validator(Path(validator_id): Path<String>) -> impl IntoResponse {
+        match validator_id.parse::<u64>() {
+            Ok(index) if index < 100 => {
+                Json(json!({ "data": validator_json(index) })).into_response()
+            }
+            _ => (
+                axum::http::StatusCode::NOT_FOUND,
+                Json(json!({ "code": 404, "message": "Validator not found" })),
+            )
+                .into_response(),
+        }
+    }
+
+    async fn validators(
+        State(requests): State<Requests>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Json<Value> {
+        let ids = query.get("id").cloned().unwrap_or_default();
+        requests.lock().unwrap().push(ids.clone());
+        let data: Vec<Value> = ids
+            .split(',')
+            .map(|id| validator_json(id.parse().unwrap()))
+            .collect();
+        Json(json!({ "data": data }))
+    }
+
+    /// Balances without the `data` envelope
+    async fn validator_balances() -> Json<Value> {
+        Json(json!([{ "index": "0", "balance": "32000000000" }]))
+    }
+
+    /// Serve a beacon node under a `/beacon` path prefix
+    async fn serve() -> (BeaconClient, Requests) {
+        let requests = Requests::default();
+        let states = Router::new()
+            .route("/validators", get(validators))
+            .route("/validators/:validator_id", get(validator))
+            .route("/validator_balances", get(validator_balances))
+            .route("/finality_checkpoints", get(finality_checkpoints));
+        let app = Router::new()
+            .route("/beacon/eth/v1/beacon/genesis", get(genesis))
+            .nest("/beacon/eth/v1/beacon/states/head", states)
+            .with_state(requests.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = BeaconClient::new(&format!("http://{addr}/beacon")).unwrap();
+        (client, requests)
+    }
+
+    #[tokio::test]
+    async fn unwraps_data_envelope() {
+        let (client, _) = serve().await;
+        let genesis = client.genesis().await.unwrap();
+        assert_eq!(genesis.genesis_time, 1695902400);
+        assert_eq!(genesis.genesis_fork_version, "0x01017000");
+    }
+
+    #[tokio::test]
+    async fn not_found_is_none() {
+        let (client, _) = serve().await;
+        let validator = client
+            .validator(&StateId::Head, &ValidatorId::Index(7))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(validator.index, 7);
+        assert_eq!(validator.status, ValidatorStatus::ActiveOngoing);
+
+        let validator = client
+            .validator(&StateId::Head, &ValidatorId::Index(100))
+            .await
+            .unwrap();
+        assert!(validator.is_none());
+    }
+
+    #[tokio::test]
+    async fn not_found_is_an_error_where_required() {
+        let (client, _) = serve().await;
+        let err = client.state_fork(&StateId::Head).await.unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::BeaconFetchForkError
+        );
+        assert!(err.to_string().ends_with("not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn chunks_validator_ids() {
+        let (client, requests) = serve().await;
+        let validator_ids: Vec<ValidatorId> = (0..130).map(ValidatorId::Index).collect();
+        let validators = client
+            .validators(&StateId::Head, &validator_ids)
+            .await
+            .unwrap();
+        let indices: Vec<u64> = validators.iter().map(|validator| validator.index).collect();
+        assert_eq!(indices, (0..130).collect::<Vec<u64>>());
+
+        let chunk_sizes: Vec<usize> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|ids| ids.split(',').count())
+            .collect();
+        assert_eq!(chunk_sizes, vec![64, 64, 2]);
+    }
+
+    #[tokio::test]
+    async fn maps_error_body() {
+        let (client, _) = serve().await;
+        let err = client
+            .finality_checkpoints(&StateId::Head)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::BeaconFetchFinalityError
+        );
+        assert_eq!(
+            err.to_string(),
+            "GET eth/v1/beacon/states/head/finality_checkpoints failed with \
+             500 Internal Server Error: State not available"
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_missing_envelope() {
+        let (client, _) = serve().await;
+        let err = client
+            .validator_balances(&StateId::Head, &[ValidatorId::Index(0)])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::BeaconParseValidatorErrro
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use ethers::abi::{parse_abi, Abi};
+use ethers::contract::Contract;
+use ethers::prelude::{Address, Bytes, JsonRpcClient, Provider, H256};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::utils::ethereum::{get_code, get_storage_at};
+
+/// Addresses of the contracts a PufferProtocol deployment depends on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PufferContracts {
+    pub puffer_protocol: Address,
+    pub puffer_oracle: Address,
+    pub validator_ticket: Address,
+    pub guardian_module: Address,
+    pub puffer_vault: Address,
+    pub puffer_module_manager: Address,
+    pub beacon_deposit_contract: Address,
+}
+
+/// Cached contracts, with the PufferProtocol implementation they were read from.
+/// The immutables can only change with an upgrade, which changes the implementation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct CachedContracts {
+    #[serde(default)]
+    implementation: Option<Address>,
+    #[serde(flatten)]
+    contracts: PufferContracts,
+}
+
+/// Cache file: "<chain id>:<puffer protocol address>" -> contracts
+type ContractsCache = BTreeMap<String, CachedContracts>;
+
+const IMMUTABLES: [&str; 6] = [
+    "PUFFER_ORACLE",
+    "VALIDATOR_TICKET",
+    "GUARDIAN_MODULE",
+    "PUFFER_VAULT",
+    "PUFFER_MODULE_MANAGER",
+    "BEACON_DEPOSIT_CONTRACT",
+];
+
+fn immutables_abi() -> Abi {
+    let signatures: Vec<String> = IMMUTABLES
+        .iter()
+        .map(|name| format!("function {name}() external view returns (address)"))
+        .collect();
+    let signatures: Vec<&str> = signatures.iter().map(String::as_str).collect();
+    parse_abi(&signatures).expect("valid immutables ABI")
+}
+
+/// `~/.coral/contracts.json`
+pub fn default_cache_path() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".coral").join("contracts.json"))
+}
+
+fn cache_key(chain_id: u64, puffer_protocol_address: Address) -> String {
+    format!("{chain_id}:{puffer_protocol_address:?}")
+}
+
+fn read_cache(path: &Path) -> ContractsCache {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache(path: &Path, cache: &ContractsCache) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            let content = serde_json::to_string_pretty(cache).unwrap_or_default();
+            std::fs::write(path, content)
+        });
+    if let Err(err) = result {
+        tracing::warn!(
+            "Failed to write contracts cache '{}': {err}",
+            path.display()
+        );
+    }
+}
+
+/// Read the contract addresses from the PufferProtocol immutables
+pub async fn fetch_puffer_contracts<J>(
+    provider: Arc<Provider<J>>,
+    puffer_protocol_address: Address,
+) -> AppServerResult<PufferContracts>
+where
+    J: JsonRpcClient + 'static,
+{
+    let contract = Contract::new(puffer_protocol_address, immutables_abi(), provider);
+
+    let mut addresses = Vec::with_capacity(IMMUTABLES.len());
+    for name in IMMUTABLES {
+        let call = contract.method::<_, Address>(name, ()).map_err(|err| {
+            let error_msg = format!("Failed to encode {name} call");
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ServerErrorCode::EvmCallError,
+                format!("{error_msg}: {err}"),
+            )
+        })?;
+        let address = call.call().await.map_err(|err| {
+            let error_msg = format!("Failed to fetch PufferProtocol.{name}");
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ServerErrorCode::EvmCallError,
+                format!("{error_msg}: {err}"),
+            )
+        })?;
+        addresses.push(address);
+    }
+
+    Ok(PufferContracts {
+        puffer_protocol: puffer_protocol_address,
+        puffer_oracle: addresses[0],
+        validator_ticket: addresses[1],
+        guardian_module: addresses[2],
+        puffer_vault: addresses[3],
+        puffer_module_manager: addresses[4],
+        beacon_deposit_contract: addresses[5],
+    })
+}
+
+/// Contract addresses of a PufferProtocol deployment, cached per chain id in `cache_path`.
+/// A cached entry is only used while the PufferProtocol implementation is unchanged,
+/// which costs a single storage read. `refresh` ignores the cached entry.
+pub async fn get_puffer_contracts<J>(
+    provider: Arc<Provider<J>>,
+    chain_id: u64,
+    puffer_protocol_address: Address,
+    cache_path: Option<&Path>,
+    refresh: bool,
+) -> AppServerResult<PufferContracts>
+where
+    J: JsonRpcClient + 'static,
+{
+    let key = cache_key(chain_id, puffer_protocol_address);
+    let mut cache = cache_path.map(read_cache).unwrap_or_default();
+    let implementation = get_implementation(&provider, puffer_protocol_address).await?;
+    if !refresh {
+        if let Some(cached) = cache.get(&key) {
+            if cached.implementation == implementation {
+                return Ok(cached.contracts);
+            }
+            tracing::info!(
+                "PufferProtocol implementation changed, reading the contract addresses again"
+            );
+        }
+    }
+
+    let contracts = fetch_puffer_contracts(provider, puffer_protocol_address).await?;
+    if let Some(cache_path) = cache_path {
+        let cached = CachedContracts {
+            implementation,
+            contracts,
+        };
+        cache.insert(key, cached);
+        write_cache(cache_path, &cache);
+    }
+    Ok(contracts)
+}
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+pub const ERC1967_IMPLEMENTATION_SLOT: [u8; 32] = [
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// Implementation in the ERC-1967 slot of a proxy, `None` if the contract is not a proxy
+pub async fn get_implementation<J, E>(
+    provider: &Provider<J>,
+    address: Address,
+) -> AppServerResult<Option<Address>>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let slot = get_storage_at(provider, address, H256(ERC1967_IMPLEMENTATION_SLOT)).await?;
+    Ok(Some(Address::from(slot)).filter(|address| !address.is_zero()))
+}
+
+/// A deployed contract, resolved through its ERC-1967 proxy if it is one
+#[derive(Clone, Debug)]
+pub struct DeployedContract {
+    pub address: Address,
+    pub implementation: Option<Address>,
+    pub upgrade_interface_version: Option<String>,
+    /// Code of the implementation for proxies, else of the contract itself
+    pub code: Bytes,
+}
+
+/// Read the implementation slot, `UPGRADE_INTERFACE_VERSION` and the code of a contract.
+/// `UPGRADE_INTERFACE_VERSION` is `None` when the call reverts, e.g. for non-UUPS contracts.
+pub async fn fetch_deployed_contract<J, E>(
+    provider: Arc<Provider<J>>,
+    address: Address,
+) -> AppServerResult<DeployedContract>
+where
+    J: JsonRpcClient<Error = E> + 'static,
+{
+    let implementation = get_implementation(&provider, address).await?;
+
+    let abi = parse_abi(&["function UPGRADE_INTERFACE_VERSION() external view returns (string)"])
+        .expect("valid UPGRADE_INTERFACE_VERSION ABI");
+    let contract = Contract::new(address, abi, provider.clone());
+    let upgrade_interface_version =
+        match contract.method::<_, String>("UPGRADE_INTERFACE_VERSION", ()) {
+            Ok(call) => call.call().await.ok(),
+            Err(_) => None,
+        };
+
+    let code = get_code(&provider, implementation.unwrap_or(address)).await?;
+    Ok(DeployedContract {
+        address,
+        implementation,
+        upgrade_interface_version,
+        code,
+    })
+}
+
+/// Values pushed by PUSH1 to PUSH4, which is how the solidity dispatcher
+/// compares function selectors. Push data is skipped so it is not read as opcodes.
+pub fn pushed_selectors(code: &[u8]) -> HashSet<u32> {
+    const PUSH1: u8 = 0x60;
+    const PUSH4: u8 = 0x63;
+    const PUSH32: u8 = 0x7f;
+
+    let mut selectors = HashSet::new();
+    let mut i = 0;
+    while i < code.len() {
+        let opcode = code[i];
+        i += 1;
+        if !(PUSH1..=PUSH32).contains(&opcode) {
+            continue;
+        }
+        let size = (opcode - PUSH1 + 1) as usize;
+        if opcode <= PUSH4 && i + size <= code.len() {
+            let value = code[i..i + size]
+                .iter()
+                .fold(0u32, |acc, byte| (acc << 8) | u32::from(*byte));
+            selectors.insert(value);
+        }
+        i += size;
+    }
+    selectors
+}
+
+/// Functions of the ABI whose selector does not appear in the code, as `name(types)` signatures
+pub fn missing_functions(abi: &Abi, code: &[u8]) -> Vec<String> {
+    let selectors = pushed_selectors(code);
+    let mut missing: Vec<String> = abi
+        .functions()
+        .filter(|function| !selectors.contains(&u32::from_be_bytes(function.short_signature())))
+        .map(|function| function.signature())
+        .collect();
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::Token;
+    use ethers::providers::MockProvider;
+
+    use super::*;
+
+    fn contracts(seed: u64) -> PufferContracts {
+        let address = |i: u64| Address::from_low_u64_be(seed * 100 + i);
+        PufferContracts {
+            puffer_protocol: Address::from_low_u64_be(1),
+            puffer_oracle: address(1),
+            validator_ticket: address(2),
+            guardian_module: address(3),
+            puffer_vault: address(4),
+            puffer_module_manager: address(5),
+            beacon_deposit_contract: address(6),
+        }
+    }
+
+    fn cache_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "coral-contracts-{name}-{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn write_entry(path: &Path, implementation: Option<Address>, contracts: PufferContracts) {
+        let mut cache = ContractsCache::new();
+        let cached = CachedContracts {
+            implementation,
+            contracts,
+        };
+        cache.insert(cache_key(1, contracts.puffer_protocol), cached);
+        write_cache(path, &cache);
+    }
+
+    /// The mock pops responses from the back, so they are pushed in reverse order
+    fn push_immutables(mock: &MockProvider, contracts: PufferContracts) {
+        let addresses = [
+            contracts.puffer_oracle,
+            contracts.validator_ticket,
+            contracts.guardian_module,
+            contracts.puffer_vault,
+            contracts.puffer_module_manager,
+            contracts.beacon_deposit_contract,
+        ];
+        for address in addresses.iter().rev() {
+            let data = Bytes::from(ethers::abi::encode(&[Token::Address(*address)]));
+            mock.push::<Bytes, _>(data).unwrap();
+        }
+    }
+
+    fn push_implementation(mock: &MockProvider, implementation: Address) {
+        mock.push::<H256, _>(H256::from(implementation)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn uses_cache_while_implementation_is_unchanged() {
+        let path = cache_file("unchanged");
+        let implementation = Address::from_low_u64_be(0xaa);
+        write_entry(&path, Some(implementation), contracts(1));
+
+        // no eth_call responses: reading the immutables would fail
+        let (provider, mock) = Provider::mocked();
+        push_implementation(&mock, implementation);
+        let result = get_puffer_contracts(
+            Arc::new(provider),
+            1,
+            Address::from_low_u64_be(1),
+            Some(&path),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, contracts(1));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn refetches_after_upgrade() {
+        let path = cache_file("upgraded");
+        write_entry(&path, Some(Address::from_low_u64_be(0xaa)), contracts(1));
+
+        let (provider, mock) = Provider::mocked();
+        push_immutables(&mock, contracts(2));
+        push_implementation(&mock, Address::from_low_u64_be(0xbb));
+        let result = get_puffer_contracts(
+            Arc::new(provider),
+            1,
+            Address::from_low_u64_be(1),
+            Some(&path),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, contracts(2));
+
+        let cache = read_cache(&path);
+        let cached = cache[&cache_key(1, Address::from_low_u64_be(1))];
+        assert_eq!(cached.implementation, Some(Address::from_low_u64_be(0xbb)));
+        assert_eq!(cached.contracts, contracts(2));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -2,25 +2,56 @@ use std::sync::Arc;
 
 use axum::http::StatusCode;
 use ethers::prelude::{
-    types::{Address, Block, BlockId, BlockNumber, TransactionReceipt, H256, U256},
-    Http, JsonRpcClient, LocalWallet, Middleware, Provider, ProviderError, Signer,
-    SignerMiddleware,
+    types::{
+        Address, Block, BlockId, BlockNumber, Bytes, Filter, Log, Transaction, TransactionReceipt,
+        H256, U256,
+    },
+    JsonRpcClient, LocalWallet, Middleware, Provider, ProviderError, Signer, SignerMiddleware,
 };
-use url::Url;
 
 use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::utils::fallback_client::{FallbackClient, FallbackClientError, RpcConfig};
 
-pub fn get_provider(rpc_url: &str) -> AppServerResult<Provider<Http>> {
-    let url = Url::parse(rpc_url).map_err(|err| {
-        let error_msg = "Invalid RPC URL";
-        tracing::error!("{error_msg}: {err}");
-        ServerErrorResponse::new(
+/// Error code of a failed RPC request: timeouts and exhausted failover
+/// over all endpoints get their own code, other errors `code`
+pub fn rpc_error_code(err: &ProviderError, code: ServerErrorCode) -> ServerErrorCode {
+    let ProviderError::JsonRpcClientError(err) = err else {
+        return code;
+    };
+    let err: &(dyn std::error::Error + Send + Sync) = err.as_ref();
+    match err.downcast_ref::<FallbackClientError>() {
+        Some(err) if err.is_timeout() => ServerErrorCode::EvmRequestTimeout,
+        Some(FallbackClientError::Exhausted { .. }) => ServerErrorCode::EvmFailoverExhausted,
+        _ => code,
+    }
+}
+
+/// Split a comma-separated list of RPC endpoints
+pub fn split_rpc_urls(rpc_url: &str) -> AppServerResult<Vec<String>> {
+    let endpoints: Vec<String> = rpc_url
+        .split(',')
+        .map(str::trim)
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(str::to_string)
+        .collect();
+    if endpoints.is_empty() {
+        let error_msg = "No RPC URL given";
+        tracing::error!("{error_msg}");
+        return Err(ServerErrorResponse::new(
             StatusCode::BAD_REQUEST,
             ServerErrorCode::HttpUrlError,
-            err.to_string(),
-        )
-    })?;
-    let transport = Http::new(url);
+            error_msg.to_string(),
+        ));
+    }
+    Ok(endpoints)
+}
+
+/// Provider over one or more comma-separated RPC endpoints, failing over between them.
+/// Each is an HTTP or WebSocket URL, or an IPC socket path.
+/// Timeout and retries are those of [`RpcConfig::current`].
+pub async fn get_provider(rpc_url: &str) -> AppServerResult<Provider<FallbackClient>> {
+    let endpoints = split_rpc_urls(rpc_url)?;
+    let transport = FallbackClient::connect(&endpoints, RpcConfig::current()).await?;
     let provider = Provider::new(transport);
     Ok(provider)
 }
@@ -46,7 +77,7 @@ where
         tracing::error!("{error_msg}: {err}");
         ServerErrorResponse::new(
             StatusCode::INTERNAL_SERVER_ERROR,
-            ServerErrorCode::EvmFetchChainIdError,
+            rpc_error_code(&err, ServerErrorCode::EvmFetchChainIdError),
             err.to_string(),
         )
     })?;
@@ -68,7 +99,7 @@ where
             tracing::error!("{error_msg}: {err}");
             ServerErrorResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                ServerErrorCode::EvmFetchBlockError,
+                rpc_error_code(&err, ServerErrorCode::EvmFetchBlockError),
                 err.to_string(),
             )
         })?
@@ -100,7 +131,7 @@ where
             tracing::error!("{error_msg}: {err}");
             ServerErrorResponse::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                ServerErrorCode::EvmGetBalanceError,
+                rpc_error_code(&err, ServerErrorCode::EvmGetBalanceError),
                 err.to_string(),
             )
         })?;
@@ -116,7 +147,7 @@ pub fn get_transaction_receipt(
             tracing::error!("{error_msg}: {err}");
             ServerErrorResponse::new(
                 StatusCode::BAD_REQUEST,
-                ServerErrorCode::EvmWaitForTransactionError,
+                rpc_error_code(&err, ServerErrorCode::EvmWaitForTransactionError),
                 err.to_string(),
             )
         })?
@@ -131,3 +162,138 @@ pub fn get_transaction_receipt(
         })?;
     Ok(tx)
 }
+
+/// Parse an address given on the command line.
+/// `name` is used in the error message, e.g. "Puffer Protocol"
+pub fn parse_address(name: &str, address: &str) -> AppServerResult<Address> {
+    address.parse().map_err(|_| {
+        let error_msg = format!("Invalid {name} address: '{address}'");
+        tracing::error!("{error_msg}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ParseError,
+            error_msg,
+        )
+    })
+}
+
+pub async fn get_nonce<J, E>(provider: &Provider<J>, address: Address) -> AppServerResult<U256>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let nonce = provider
+        .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+        .await
+        .map_err(|err| {
+            let error_msg = "Failed to fetch nonce";
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                rpc_error_code(&err, ServerErrorCode::EvmFetchTransactionError),
+                err.to_string(),
+            )
+        })?;
+    Ok(nonce)
+}
+
+pub async fn get_block_number<J, E>(provider: &Provider<J>) -> AppServerResult<u64>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let block_number = provider.get_block_number().await.map_err(|err| {
+        let error_msg = "Failed to fetch block number";
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            rpc_error_code(&err, ServerErrorCode::EvmFetchBlockError),
+            err.to_string(),
+        )
+    })?;
+    Ok(block_number.as_u64())
+}
+
+pub async fn get_logs<J, E>(provider: &Provider<J>, filter: &Filter) -> AppServerResult<Vec<Log>>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let logs = provider.get_logs(filter).await.map_err(|err| {
+        let error_msg = "Failed to fetch logs";
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            rpc_error_code(&err, ServerErrorCode::EvmFetchLogError),
+            err.to_string(),
+        )
+    })?;
+    Ok(logs)
+}
+
+pub async fn get_transaction<J, E>(
+    provider: &Provider<J>,
+    tx_hash: H256,
+) -> AppServerResult<Transaction>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let tx = provider
+        .get_transaction(tx_hash)
+        .await
+        .map_err(|err| {
+            let error_msg = "Failed to fetch transaction";
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                rpc_error_code(&err, ServerErrorCode::EvmFetchTransactionError),
+                err.to_string(),
+            )
+        })?
+        .ok_or_else(|| {
+            let error_msg = format!("Transaction {tx_hash:?} not found");
+            tracing::error!("{error_msg}");
+            ServerErrorResponse::new(
+                StatusCode::NOT_FOUND,
+                ServerErrorCode::EvmFetchTransactionError,
+                error_msg,
+            )
+        })?;
+    Ok(tx)
+}
+
+pub async fn get_code<J, E>(provider: &Provider<J>, address: Address) -> AppServerResult<Bytes>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let code = provider.get_code(address, None).await.map_err(|err| {
+        let error_msg = format!("Failed to fetch code of {address:?}");
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            rpc_error_code(&err, ServerErrorCode::EvmFetchCodeError),
+            err.to_string(),
+        )
+    })?;
+    Ok(code)
+}
+
+pub async fn get_storage_at<J, E>(
+    provider: &Provider<J>,
+    address: Address,
+    slot: H256,
+) -> AppServerResult<H256>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let value = provider
+        .get_storage_at(address, slot, None)
+        .await
+        .map_err(|err| {
+            let error_msg = format!("Failed to fetch storage slot {slot:?} of {address:?}");
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                rpc_error_code(&err, ServerErrorCode::EvmFetchStorageError),
+                err.to_string(),
+            )
+        })?;
+    Ok(value)
+}
@@ -0,0 +1,530 @@
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use ethers::providers::{
+    HttpRateLimitRetryPolicy, JsonRpcClient, JsonRpcError, ProviderError, PubsubClient,
+    RetryPolicy, RpcError,
+};
+use ethers::types::U256;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use url::Url;
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::utils::transport::{NotificationStream, Transport, TransportError};
+
+/// Timeout and retries of requests to the RPC endpoints
+#[derive(Clone, Debug)]
+pub struct RpcConfig {
+    /// Per request and endpoint
+    pub timeout: Duration,
+    /// Rounds over all endpoints after the first one
+    pub max_retries: u32,
+    /// Doubled after every round
+    pub initial_backoff: Duration,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+static RPC_CONFIG: OnceLock<RpcConfig> = OnceLock::new();
+
+impl RpcConfig {
+    /// Config set with [`RpcConfig::init`], else [`RpcConfig::from_env`]
+    pub fn current() -> Self {
+        RPC_CONFIG.get().cloned().unwrap_or_else(Self::from_env)
+    }
+
+    /// Set the config of every provider created afterwards, only the first call has an effect
+    pub fn init(self) {
+        if RPC_CONFIG.set(self).is_err() {
+            tracing::warn!("RPC config is already set");
+        }
+    }
+
+    /// Defaults, overridden by `$CORAL_RPC_TIMEOUT` (seconds) and `$CORAL_RPC_MAX_RETRIES`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(timeout) = env_var::<u64>("CORAL_RPC_TIMEOUT") {
+            config.timeout = Duration::from_secs(timeout);
+        }
+        if let Some(max_retries) = env_var::<u32>("CORAL_RPC_MAX_RETRIES") {
+            config.max_retries = max_retries;
+        }
+        config
+    }
+}
+
+fn env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid ${name} '{value}'");
+            None
+        }
+    }
+}
+
+/// Methods that broadcast a transaction are sent once, to the endpoint that last answered:
+/// after a timeout the transaction may have been broadcast, sending it again would fail
+/// with "already known" or "nonce too low"
+const SEND_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// Host and port of a URL endpoint, so API keys in the path are not logged.
+/// IPC endpoints are named by their path.
+fn endpoint_name(endpoint: &str) -> String {
+    let Ok(url) = Url::parse(endpoint) else {
+        return endpoint.to_string();
+    };
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        _ => url.scheme().to_string(),
+    }
+}
+
+#[derive(Debug)]
+pub enum FallbackClientError {
+    Transport {
+        endpoint: String,
+        err: TransportError,
+    },
+    Timeout {
+        endpoint: String,
+        timeout: Duration,
+    },
+    SerdeJson(serde_json::Error),
+    /// JSON-RPC batch requests without an HTTP endpoint
+    BatchNotSupported,
+    /// Every endpoint failed in every round
+    Exhausted {
+        attempts: usize,
+        last_error: Box<FallbackClientError>,
+    },
+}
+
+impl FallbackClientError {
+    /// Whether the request, or the last attempt of an exhausted failover, timed out
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Timeout { .. } => true,
+            Self::Exhausted { last_error, .. } => last_error.is_timeout(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FallbackClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport { endpoint, err } => write!(f, "{endpoint}: {err}"),
+            Self::Timeout { endpoint, timeout } => {
+                write!(f, "{endpoint}: request timed out after {timeout:?}")
+            }
+            Self::SerdeJson(err) => write!(f, "Failed to serialize request: {err}"),
+            Self::BatchNotSupported => {
+                write!(f, "JSON-RPC batch requests need an HTTP endpoint")
+            }
+            Self::Exhausted {
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "All RPC endpoints failed after {attempts} attempts, last error: {last_error}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FallbackClientError {}
+
+impl RpcError for FallbackClientError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Transport { err, .. } => err.as_error_response(),
+            Self::Exhausted { last_error, .. } => last_error.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Transport { err, .. } => err.as_serde_error(),
+            Self::SerdeJson(err) => Some(err),
+            Self::Exhausted { last_error, .. } => last_error.as_serde_error(),
+            _ => None,
+        }
+    }
+}
+
+impl From<FallbackClientError> for ProviderError {
+    fn from(err: FallbackClientError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Endpoint {
+    name: String,
+    transport: Transport,
+}
+
+/// JSON-RPC client over several endpoints, each over HTTP, WebSocket or IPC.
+///
+/// Requests go to the endpoint that last answered. On a timeout, a connection error,
+/// a rate limit or an unreadable response, the next endpoint is tried, and after a round
+/// over all of them the client waits with exponential backoff before the next round.
+/// JSON-RPC errors such as reverts are returned as is.
+/// Transactions are sent once, without failover, see [`SEND_METHODS`].
+/// Clones share the endpoint that last answered.
+#[derive(Clone, Debug)]
+pub struct FallbackClient {
+    endpoints: Arc<Vec<Endpoint>>,
+    active: Arc<AtomicUsize>,
+    config: RpcConfig,
+    /// Sends JSON-RPC batch requests to the HTTP endpoints
+    http_client: reqwest::Client,
+}
+
+impl FallbackClient {
+    /// Connect to every endpoint, see [`Transport::connect`]
+    pub async fn connect(endpoints: &[String], config: RpcConfig) -> AppServerResult<Self> {
+        if endpoints.is_empty() {
+            let error_msg = "At least one RPC endpoint is required";
+            tracing::error!("{error_msg}");
+            return Err(ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::HttpUrlError,
+                error_msg.to_string(),
+            ));
+        }
+        let mut connected = Vec::with_capacity(endpoints.len());
+        for endpoint in endpoints {
+            connected.push(Endpoint {
+                name: endpoint_name(endpoint),
+                transport: Transport::connect(endpoint).await?,
+            });
+        }
+        Ok(Self {
+            endpoints: Arc::new(connected),
+            active: Arc::new(AtomicUsize::new(0)),
+            config,
+            http_client: reqwest::Client::new(),
+        })
+    }
+
+    /// Whether the endpoint requests currently go to can subscribe
+    pub fn supports_subscriptions(&self) -> bool {
+        self.active_endpoint().transport.supports_subscriptions()
+    }
+
+    fn active_endpoint(&self) -> &Endpoint {
+        &self.endpoints[self.active.load(Ordering::Relaxed)]
+    }
+
+    /// Whether any endpoint can serve JSON-RPC batch requests
+    pub fn supports_batches(&self) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.transport.batch_url().is_some())
+    }
+
+    /// Send a JSON-RPC batch request, an array of requests, and parse the array of responses.
+    /// Failed over like single requests, between the HTTP endpoints only.
+    pub async fn batch_request<R>(
+        &self,
+        requests: &[serde_json::Value],
+    ) -> Result<R, FallbackClientError>
+    where
+        R: DeserializeOwned + Send,
+    {
+        if !self.supports_batches() {
+            return Err(FallbackClientError::BatchNotSupported);
+        }
+        self.with_failover("JSON-RPC batch", |transport| {
+            let url = transport.batch_url()?.clone();
+            let http_client = &self.http_client;
+            Some(async move {
+                let response = http_client
+                    .post(url)
+                    .json(requests)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .map_err(TransportError::Batch)?;
+                response.json().await.map_err(TransportError::Batch)
+            })
+        })
+        .await
+    }
+
+    /// Send a request once to the endpoint that last answered
+    async fn send_once<R>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<R, FallbackClientError>
+    where
+        R: DeserializeOwned + Send,
+    {
+        let endpoint = self.active_endpoint();
+        let request = endpoint.transport.request(method, params);
+        match tokio::time::timeout(self.config.timeout, request).await {
+            Ok(Ok(result)) => {
+                tracing::debug!("{method} served by {}", endpoint.name);
+                Ok(result)
+            }
+            Ok(Err(err)) => Err(FallbackClientError::Transport {
+                endpoint: endpoint.name.clone(),
+                err,
+            }),
+            Err(_) => Err(FallbackClientError::Timeout {
+                endpoint: endpoint.name.clone(),
+                timeout: self.config.timeout,
+            }),
+        }
+    }
+
+    /// Send a request with `send` to the endpoint that last answered, failing over on transient
+    /// errors. Endpoints for which `send` returns `None` are skipped.
+    async fn with_failover<'a, R, F, Fut>(
+        &'a self,
+        method: &str,
+        send: F,
+    ) -> Result<R, FallbackClientError>
+    where
+        F: Fn(&'a Transport) -> Option<Fut>,
+        Fut: Future<Output = Result<R, TransportError>> + 'a,
+    {
+        let start = self.active.load(Ordering::Relaxed);
+        let mut backoff = self.config.initial_backoff;
+        let mut last_error = None;
+        let mut attempts = 0;
+
+        for round in 0..=self.config.max_retries {
+            if round > 0 {
+                tracing::debug!("Retrying {method} in {backoff:?}");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            for offset in 0..self.endpoints.len() {
+                let index = (start + offset) % self.endpoints.len();
+                let endpoint = &self.endpoints[index];
+                let Some(request) = send(&endpoint.transport) else {
+                    continue;
+                };
+
+                attempts += 1;
+                let response = tokio::time::timeout(self.config.timeout, request).await;
+                let err = match response {
+                    Ok(Ok(result)) => {
+                        tracing::debug!("{method} served by {}", endpoint.name);
+                        self.active.store(index, Ordering::Relaxed);
+                        return Ok(result);
+                    }
+                    Ok(Err(err)) if !err.is_transient() => {
+                        return Err(FallbackClientError::Transport {
+                            endpoint: endpoint.name.clone(),
+                            err,
+                        });
+                    }
+                    Ok(Err(err)) => {
+                        if let TransportError::Http(http_err) = &err {
+                            if let Some(hint) = HttpRateLimitRetryPolicy.backoff_hint(http_err) {
+                                backoff = backoff.max(hint);
+                            }
+                        }
+                        FallbackClientError::Transport {
+                            endpoint: endpoint.name.clone(),
+                            err,
+                        }
+                    }
+                    Err(_) => FallbackClientError::Timeout {
+                        endpoint: endpoint.name.clone(),
+                        timeout: self.config.timeout,
+                    },
+                };
+                tracing::warn!("{method} failed on {}: {err}", endpoint.name);
+                last_error = Some(err);
+            }
+        }
+        let last_error = last_error.expect("at least one endpoint was tried");
+        Err(FallbackClientError::Exhausted {
+            attempts,
+            last_error: Box::new(last_error),
+        })
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for FallbackClient {
+    type Error = FallbackClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params).map_err(FallbackClientError::SerdeJson)?;
+        if SEND_METHODS.contains(&method) {
+            return self.send_once(method, params).await;
+        }
+        self.with_failover(method, |transport| {
+            Some(transport.request(method, params.clone()))
+        })
+        .await
+    }
+}
+
+/// Subscriptions go to the endpoint that served the `eth_subscribe` request,
+/// they are not failed over
+impl PubsubClient for FallbackClient {
+    type NotificationStream = NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        let endpoint = self.active_endpoint();
+        endpoint
+            .transport
+            .subscribe(id)
+            .map_err(|err| FallbackClientError::Transport {
+                endpoint: endpoint.name.clone(),
+                err,
+            })
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        let endpoint = self.active_endpoint();
+        endpoint
+            .transport
+            .unsubscribe(id)
+            .map_err(|err| FallbackClientError::Transport {
+                endpoint: endpoint.name.clone(),
+                err,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use ethers::providers::{Middleware, Provider};
+    use ethers::types::Bytes;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::utils::ethereum::rpc_error_code;
+
+    fn config() -> RpcConfig {
+        RpcConfig {
+            timeout: Duration::from_millis(200),
+            max_retries: 1,
+            initial_backoff: Duration::from_millis(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_endpoints() {
+        let err = FallbackClient::connect(&[], config()).await.unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::HttpUrlError
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_exhausted_failover() {
+        // nothing listens on the port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = FallbackClient::connect(&[endpoint], config())
+            .await
+            .unwrap();
+        let err = Provider::new(client).get_block_number().await.unwrap_err();
+        assert_eq!(
+            rpc_error_code(&err, ServerErrorCode::EvmFetchBlockError),
+            ServerErrorCode::EvmFailoverExhausted
+        );
+        assert!(err.to_string().contains("after 2 attempts"));
+    }
+
+    #[tokio::test]
+    async fn maps_timeout() {
+        // accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+        let client = FallbackClient::connect(&[endpoint], config())
+            .await
+            .unwrap();
+        let err = Provider::new(client).get_block_number().await.unwrap_err();
+        assert_eq!(
+            rpc_error_code(&err, ServerErrorCode::EvmFetchBlockError),
+            ServerErrorCode::EvmRequestTimeout
+        );
+        drop(listener);
+    }
+
+    /// Endpoint answering every request with block number 16, counting requests
+    async fn serve_counting() -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app =
+            Router::new().route(
+                "/",
+                post(move |Json(request): Json<Value>| {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    async move {
+                        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x10" }))
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (endpoint, requests)
+    }
+
+    #[tokio::test]
+    async fn sends_transactions_once() {
+        // accepts connections but never answers
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent_endpoint = format!("http://{}", silent.local_addr().unwrap());
+        let (endpoint, requests) = serve_counting().await;
+
+        let client = FallbackClient::connect(&[silent_endpoint, endpoint], config())
+            .await
+            .unwrap();
+        let provider = Provider::new(client);
+        let err = provider
+            .send_raw_transaction(Bytes::from_static(&[0x02]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            rpc_error_code(&err, ServerErrorCode::EvmSendTransactionError),
+            ServerErrorCode::EvmRequestTimeout
+        );
+        assert_eq!(requests.load(Ordering::Relaxed), 0);
+
+        // reads fail over
+        let block_number = provider.get_block_number().await.unwrap();
+        assert_eq!(block_number.as_u64(), 16);
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+        drop(silent);
+    }
+}
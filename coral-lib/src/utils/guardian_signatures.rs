@@ -0,0 +1,422 @@
+use axum::http::StatusCode;
+use ethers::abi::{self, Token};
+use ethers::prelude::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::{hash_message, keccak256};
+use serde::Serialize;
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+
+/// Return values of `PufferProtocol.getPayload(moduleName, usingEnclave)`
+#[derive(Clone, Debug)]
+pub struct ProvisioningPayload {
+    pub guardian_enclave_pubkeys: Vec<Bytes>,
+    pub withdrawal_credentials: Bytes,
+    pub threshold: U256,
+    pub eth_amount: U256,
+}
+
+/// Guardian addresses read from the GuardianModule
+#[derive(Clone, Debug)]
+pub struct GuardianAddresses {
+    pub guardians: Vec<Address>,
+    pub enclave_addresses: Vec<Address>,
+    pub threshold: U256,
+}
+
+/// The validator guardians sign off on in `provisionNode`
+#[derive(Clone, Debug)]
+pub struct ProvisioningValidator {
+    pub pub_key: Bytes,
+    pub signature: Bytes,
+    pub deposit_data_root: [u8; 32],
+    pub puffer_module_index: U256,
+}
+
+/// Which guardian address set a recovered signer belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerRole {
+    Enclave,
+    Guardian,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecoveredSignature {
+    pub index: usize,
+    pub signer: Option<Address>,
+    pub role: Option<SignerRole>,
+    pub duplicate: bool,
+    pub error: Option<String>,
+}
+
+/// Signatures checked against one guardian address set.
+/// `valid_signatures` counts unique signers in that set, as the GuardianModule does.
+#[derive(Clone, Debug, Serialize)]
+pub struct SignatureReport {
+    pub signed_message_hash: H256,
+    pub expected_role: SignerRole,
+    pub signatures: Vec<RecoveredSignature>,
+    pub valid_signatures: usize,
+    pub threshold: U256,
+    pub warnings: Vec<String>,
+}
+
+fn signature_error(error_code: ServerErrorCode, error_msg: String) -> ServerErrorResponse {
+    tracing::error!("{error_msg}");
+    ServerErrorResponse::new(StatusCode::BAD_REQUEST, error_code, error_msg)
+}
+
+/// `LibGuardianMessages._getBeaconDepositMessageToBeSigned`
+pub fn provision_node_message_hash(
+    validator: &ProvisioningValidator,
+    withdrawal_credentials: &Bytes,
+) -> H256 {
+    let encoded = abi::encode(&[
+        Token::Bytes(validator.pub_key.to_vec()),
+        Token::Bytes(withdrawal_credentials.to_vec()),
+        Token::Bytes(validator.signature.to_vec()),
+        Token::FixedBytes(validator.deposit_data_root.to_vec()),
+        Token::Uint(validator.puffer_module_index),
+    ]);
+    hash_message(keccak256(encoded))
+}
+
+/// `LibGuardianMessages._getSkipProvisioningMessage`
+pub fn skip_provisioning_message_hash(module_name: [u8; 32], skipped_index: U256) -> H256 {
+    let encoded = abi::encode(&[
+        Token::FixedBytes(module_name.to_vec()),
+        Token::Uint(skipped_index),
+    ]);
+    hash_message(keccak256(encoded))
+}
+
+/// Address of an uncompressed secp256k1 public key, as registered by `rotateGuardianKey`
+pub fn enclave_address_from_pubkey(pub_key: &[u8]) -> Option<Address> {
+    if pub_key.len() != 65 || pub_key[0] != 0x04 {
+        return None;
+    }
+    let hash = keccak256(&pub_key[1..]);
+    Some(Address::from_slice(&hash[12..]))
+}
+
+fn recover_signer(signature: &[u8], signed_message_hash: H256) -> Result<Address, String> {
+    let signature = Signature::try_from(signature).map_err(|err| err.to_string())?;
+    signature
+        .recover(signed_message_hash)
+        .map_err(|err| err.to_string())
+}
+
+/// Recover the signer of each signature and count the unique ones in the expected address set
+pub fn verify_signatures(
+    signatures: &[Bytes],
+    signed_message_hash: H256,
+    guardian_addresses: &GuardianAddresses,
+    expected_role: SignerRole,
+) -> SignatureReport {
+    let mut recovered: Vec<RecoveredSignature> = Vec::with_capacity(signatures.len());
+    let mut seen: Vec<Address> = Vec::new();
+    let mut valid_signatures = 0;
+
+    for (index, signature) in signatures.iter().enumerate() {
+        let signer = match recover_signer(signature, signed_message_hash) {
+            Ok(signer) => signer,
+            Err(err) => {
+                recovered.push(RecoveredSignature {
+                    index,
+                    signer: None,
+                    role: None,
+                    duplicate: false,
+                    error: Some(err),
+                });
+                continue;
+            }
+        };
+
+        let role = if guardian_addresses.enclave_addresses.contains(&signer) {
+            SignerRole::Enclave
+        } else if guardian_addresses.guardians.contains(&signer) {
+            SignerRole::Guardian
+        } else {
+            SignerRole::Unknown
+        };
+        let duplicate = seen.contains(&signer);
+        if !duplicate {
+            seen.push(signer);
+            if role == expected_role {
+                valid_signatures += 1;
+            }
+        }
+        recovered.push(RecoveredSignature {
+            index,
+            signer: Some(signer),
+            role: Some(role),
+            duplicate,
+            error: None,
+        });
+    }
+
+    SignatureReport {
+        signed_message_hash,
+        expected_role,
+        signatures: recovered,
+        valid_signatures,
+        threshold: guardian_addresses.threshold,
+        warnings: Vec::new(),
+    }
+}
+
+/// Rebuild the `provisionNode` message from the payload and check the guardian enclave signatures.
+/// The payload is compared against the guardian set, a mismatch is reported as a warning.
+pub fn verify_provisioning_signatures(
+    payload: &ProvisioningPayload,
+    validator: &ProvisioningValidator,
+    enclave_signatures: &[Bytes],
+    guardian_addresses: &GuardianAddresses,
+) -> SignatureReport {
+    let signed_message_hash =
+        provision_node_message_hash(validator, &payload.withdrawal_credentials);
+    let mut report = verify_signatures(
+        enclave_signatures,
+        signed_message_hash,
+        guardian_addresses,
+        SignerRole::Enclave,
+    );
+
+    if payload.threshold != guardian_addresses.threshold {
+        report.warnings.push(format!(
+            "Payload threshold {} does not match the GuardianModule threshold {}",
+            payload.threshold, guardian_addresses.threshold
+        ));
+    }
+    let payload_enclave_addresses: Vec<Option<Address>> = payload
+        .guardian_enclave_pubkeys
+        .iter()
+        .map(|pub_key| enclave_address_from_pubkey(pub_key))
+        .collect();
+    for (i, address) in payload_enclave_addresses.iter().enumerate() {
+        match address {
+            Some(address) if guardian_addresses.enclave_addresses.contains(address) => {}
+            Some(address) => report.warnings.push(format!(
+                "Payload enclave public key {i} ({address:?}) is not a guardian enclave address"
+            )),
+            None => report.warnings.push(format!(
+                "Payload enclave public key {i} is not an uncompressed secp256k1 key"
+            )),
+        }
+    }
+    report
+}
+
+impl SignatureReport {
+    /// Fails the way the GuardianModule would reject the signatures
+    pub fn ensure_valid(&self) -> AppServerResult<()> {
+        if let Some(invalid) = self.signatures.iter().find(|sig| sig.error.is_some()) {
+            return Err(signature_error(
+                ServerErrorCode::GuardianInvalidSignature,
+                format!(
+                    "Invalid guardian signature {}: {}",
+                    invalid.index,
+                    invalid.error.as_deref().unwrap_or_default()
+                ),
+            ));
+        }
+        if U256::from(self.valid_signatures) < self.threshold {
+            return Err(signature_error(
+                ServerErrorCode::GuardianInvalidNumberOfSignatures,
+                format!(
+                    "Only {} valid guardian signature(s), threshold is {}",
+                    self.valid_signatures, self.threshold
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::signers::{LocalWallet, Signer};
+
+    use super::*;
+
+    const PRIVATE_KEYS: [&str; 2] = [
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    ];
+
+    fn wallet(index: usize) -> LocalWallet {
+        PRIVATE_KEYS[index].parse().unwrap()
+    }
+
+    fn validator() -> ProvisioningValidator {
+        ProvisioningValidator {
+            pub_key: Bytes::from(vec![0x11; 48]),
+            signature: Bytes::from(vec![0x33; 96]),
+            deposit_data_root: [0x44; 32],
+            puffer_module_index: U256::from(7),
+        }
+    }
+
+    fn withdrawal_credentials() -> Bytes {
+        let mut withdrawal_credentials = vec![0x01];
+        withdrawal_credentials.extend([0; 11]);
+        withdrawal_credentials.extend([0x22; 20]);
+        Bytes::from(withdrawal_credentials)
+    }
+
+    fn sign(wallet: &LocalWallet, signed_message_hash: H256) -> Bytes {
+        Bytes::from(wallet.sign_hash(signed_message_hash).unwrap().to_vec())
+    }
+
+    /// Enclave addresses of both wallets, threshold 2
+    fn guardian_addresses() -> GuardianAddresses {
+        GuardianAddresses {
+            guardians: vec![Address::repeat_byte(0x99)],
+            enclave_addresses: vec![wallet(0).address(), wallet(1).address()],
+            threshold: U256::from(2),
+        }
+    }
+
+    // expected hashes computed from the Solidity ABI encoding of
+    // `keccak256(abi.encode(...)).toEthSignedMessageHash()` in LibGuardianMessages
+    #[test]
+    fn provision_node_message_matches_lib_guardian_messages() {
+        let signed_message_hash =
+            provision_node_message_hash(&validator(), &withdrawal_credentials());
+        assert_eq!(
+            signed_message_hash,
+            "0x5464820659760f8db0b3b7c7ae160a5491185ca176778deadd862cab60ab2bd9"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn skip_provisioning_message_matches_lib_guardian_messages() {
+        let module_name = crate::structs::ModuleName::parse("PUFFER_MODULE_0").unwrap();
+        let signed_message_hash =
+            skip_provisioning_message_hash(module_name.to_bytes(), U256::from(5));
+        assert_eq!(
+            signed_message_hash,
+            "0xdddfe2d92379f4941ecca1a5cfaf1d51873d344ccb714b7a84e0eb90c12fa847"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn enclave_address_matches_wallet() {
+        let wallet = wallet(0);
+        let pub_key = wallet.signer().verifying_key().to_encoded_point(false);
+        assert_eq!(
+            enclave_address_from_pubkey(pub_key.as_bytes()),
+            Some(wallet.address())
+        );
+
+        let compressed = wallet.signer().verifying_key().to_encoded_point(true);
+        assert_eq!(enclave_address_from_pubkey(compressed.as_bytes()), None);
+    }
+
+    #[test]
+    fn recovers_signers() {
+        let signed_message_hash =
+            provision_node_message_hash(&validator(), &withdrawal_credentials());
+        let signatures = vec![
+            sign(&wallet(0), signed_message_hash),
+            sign(&wallet(1), signed_message_hash),
+        ];
+
+        let report = verify_signatures(
+            &signatures,
+            signed_message_hash,
+            &guardian_addresses(),
+            SignerRole::Enclave,
+        );
+        assert_eq!(report.signatures[0].signer, Some(wallet(0).address()));
+        assert_eq!(report.signatures[1].signer, Some(wallet(1).address()));
+        assert_eq!(report.signatures[0].role, Some(SignerRole::Enclave));
+        assert_eq!(report.valid_signatures, 2);
+        report.ensure_valid().unwrap();
+
+        // signatures of another message recover to other addresses
+        let other_hash = skip_provisioning_message_hash([0; 32], U256::zero());
+        let report = verify_signatures(
+            &signatures,
+            other_hash,
+            &guardian_addresses(),
+            SignerRole::Enclave,
+        );
+        assert_eq!(report.signatures[0].role, Some(SignerRole::Unknown));
+        assert_eq!(report.valid_signatures, 0);
+    }
+
+    #[test]
+    fn counts_duplicate_signers_once() {
+        let signed_message_hash =
+            provision_node_message_hash(&validator(), &withdrawal_credentials());
+        let signature = sign(&wallet(0), signed_message_hash);
+        let signatures = vec![signature.clone(), signature];
+
+        let report = verify_signatures(
+            &signatures,
+            signed_message_hash,
+            &guardian_addresses(),
+            SignerRole::Enclave,
+        );
+        assert!(!report.signatures[0].duplicate);
+        assert!(report.signatures[1].duplicate);
+        assert_eq!(report.valid_signatures, 1);
+        let err = report.ensure_valid().unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::GuardianInvalidNumberOfSignatures
+        );
+    }
+
+    #[test]
+    fn rejects_below_threshold() {
+        let signed_message_hash =
+            provision_node_message_hash(&validator(), &withdrawal_credentials());
+        // a guardian signature doesn't count when enclave signatures are expected
+        let mut guardian_addresses = guardian_addresses();
+        guardian_addresses.guardians = vec![wallet(1).address()];
+        guardian_addresses.enclave_addresses = vec![wallet(0).address()];
+        guardian_addresses.threshold = U256::from(2);
+        let signatures = vec![
+            sign(&wallet(0), signed_message_hash),
+            sign(&wallet(1), signed_message_hash),
+        ];
+
+        let report = verify_signatures(
+            &signatures,
+            signed_message_hash,
+            &guardian_addresses,
+            SignerRole::Enclave,
+        );
+        assert_eq!(report.signatures[1].role, Some(SignerRole::Guardian));
+        assert_eq!(report.valid_signatures, 1);
+        let err = report.ensure_valid().unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::GuardianInvalidNumberOfSignatures
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_signature() {
+        let signed_message_hash = skip_provisioning_message_hash([0; 32], U256::zero());
+        let report = verify_signatures(
+            &[Bytes::from(vec![0x01; 64])],
+            signed_message_hash,
+            &guardian_addresses(),
+            SignerRole::Enclave,
+        );
+        assert!(report.signatures[0].error.is_some());
+        let err = report.ensure_valid().unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::GuardianInvalidSignature
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ethers::utils::{hex, keccak256};
+use puffersecuresigner::client::generate_bls_keystore_handler;
+use puffersecuresigner::enclave::types::AttestFreshBlsKeyPayload;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::add_0x_prefix;
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+
+/// Evidence returned by the mock for `attest_fresh_bls_key`
+#[derive(Clone, Debug)]
+pub enum MockEvidence {
+    /// Generate a BLS keystore locally, as `keygen` does without an enclave.
+    /// The payload has no Intel evidence.
+    Local { password: String },
+    /// Return the same keygen payload, e.g. saved from a real enclave, for every request
+    Canned(serde_json::Value),
+}
+
+#[derive(Clone, Debug)]
+pub struct MockEnclaveConfig {
+    /// BLS public keys listed before any keygen
+    pub keys: Vec<String>,
+    pub evidence: MockEvidence,
+    pub healthy: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MockKey {
+    pub pubkey: String,
+    pub derivation_path: String,
+    pub readonly: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MockListKeysResponse {
+    pub data: Vec<MockKey>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MockSignatureResponse {
+    pub signature: String,
+}
+
+#[derive(Debug)]
+struct MockState {
+    keys: Mutex<Vec<String>>,
+    evidence: MockEvidence,
+    healthy: AtomicBool,
+}
+
+impl MockState {
+    fn add_key(&self, pubkey: &str) {
+        let pubkey = normalize_pubkey(pubkey);
+        let mut keys = self.keys.lock().unwrap();
+        if !keys.contains(&pubkey) {
+            keys.push(pubkey);
+        }
+    }
+
+    fn has_key(&self, pubkey: &str) -> bool {
+        self.keys
+            .lock()
+            .unwrap()
+            .contains(&normalize_pubkey(pubkey))
+    }
+}
+
+fn normalize_pubkey(pubkey: &str) -> String {
+    add_0x_prefix(pubkey).to_lowercase()
+}
+
+fn mock_error(status: StatusCode, error_msg: String) -> Response {
+    tracing::error!("{error_msg}");
+    (status, error_msg).into_response()
+}
+
+async fn health(State(state): State<Arc<MockState>>) -> StatusCode {
+    if state.healthy.load(Ordering::Relaxed) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+async fn list_keys(State(state): State<Arc<MockState>>) -> Json<MockListKeysResponse> {
+    let data = state
+        .keys
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|pubkey| MockKey {
+            pubkey: pubkey.clone(),
+            derivation_path: String::new(),
+            readonly: false,
+        })
+        .collect();
+    Json(MockListKeysResponse { data })
+}
+
+async fn attest_fresh_bls_key(
+    State(state): State<Arc<MockState>>,
+    Json(payload): Json<AttestFreshBlsKeyPayload>,
+) -> Response {
+    let keygen_payload = match &state.evidence {
+        MockEvidence::Canned(keygen_payload) => keygen_payload.clone(),
+        MockEvidence::Local { password } => {
+            let password = password.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                generate_bls_keystore_handler(payload, &password)
+            })
+            .await;
+            let keygen_payload = match result {
+                Ok(Ok(keygen_payload)) => keygen_payload,
+                Ok(Err(err)) => {
+                    let error_msg = format!("Failed to generate BLS key: {err}");
+                    return mock_error(StatusCode::INTERNAL_SERVER_ERROR, error_msg);
+                }
+                Err(err) => {
+                    let error_msg = format!("Failed to generate BLS key: {err}");
+                    return mock_error(StatusCode::INTERNAL_SERVER_ERROR, error_msg);
+                }
+            };
+            match serde_json::to_value(keygen_payload) {
+                Ok(keygen_payload) => keygen_payload,
+                Err(err) => {
+                    let error_msg = format!("Failed to serialize keygen payload: {err}");
+                    return mock_error(StatusCode::INTERNAL_SERVER_ERROR, error_msg);
+                }
+            }
+        }
+    };
+
+    if let Some(pubkey) = keygen_payload["bls_pub_key"].as_str() {
+        state.add_key(pubkey);
+    }
+    Json(keygen_payload).into_response()
+}
+
+/// Not a valid BLS signature: 96 bytes derived from the key and the request,
+/// so the same request is always answered with the same signature
+async fn sign(
+    State(state): State<Arc<MockState>>,
+    Path(pubkey): Path<String>,
+    Json(request): Json<serde_json::Value>,
+) -> Response {
+    if !state.has_key(&pubkey) {
+        let error_msg = format!("Key not found: {pubkey}");
+        return mock_error(StatusCode::NOT_FOUND, error_msg);
+    }
+    let seed = keccak256(format!("{}{request}", normalize_pubkey(&pubkey)));
+    let signature: Vec<u8> = (0u8..3)
+        .flat_map(|i| keccak256([seed.as_slice(), &[i]].concat()))
+        .collect();
+    Json(MockSignatureResponse {
+        signature: format!("0x{}", hex::encode(signature)),
+    })
+    .into_response()
+}
+
+fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route("/upcheck", get(health))
+        .route("/eth/v1/keystores", get(list_keys))
+        .route("/bls/v1/keygen", post(attest_fresh_bls_key))
+        .route("/api/v1/eth2/sign/:pubkey", post(sign))
+        .with_state(state)
+}
+
+/// Secure-Signer validator endpoints served in-process, for tests and demos
+/// of the enclave-backed commands without an SGX machine.
+/// The server stops when the `MockEnclave` is dropped.
+#[derive(Debug)]
+pub struct MockEnclave {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockEnclave {
+    /// Serve on `addr`, use port `0` to pick a free port
+    pub async fn start(config: MockEnclaveConfig, addr: SocketAddr) -> AppServerResult<Self> {
+        let listener = TcpListener::bind(addr).await.map_err(|err| {
+            let error_msg = format!("Failed to bind mock enclave to {addr}");
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ServerErrorCode::EnclaveMockServerError,
+                format!("{error_msg}: {err}"),
+            )
+        })?;
+        let addr = listener.local_addr().map_err(|err| {
+            let error_msg = "Failed to read mock enclave address";
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ServerErrorCode::EnclaveMockServerError,
+                format!("{error_msg}: {err}"),
+            )
+        })?;
+
+        let state = Arc::new(MockState {
+            keys: Mutex::new(Vec::new()),
+            evidence: config.evidence,
+            healthy: AtomicBool::new(config.healthy),
+        });
+        for pubkey in config.keys.iter() {
+            state.add_key(pubkey);
+        }
+
+        let app = router(state.clone());
+        let server = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                tracing::error!("Mock enclave stopped: {err}");
+            }
+        });
+        Ok(Self {
+            addr,
+            state,
+            server,
+        })
+    }
+
+    /// URL to pass as `--enclave-url`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Keys given in the config and generated since
+    pub fn keys(&self) -> Vec<String> {
+        self.state.keys.lock().unwrap().clone()
+    }
+
+    /// Make the health check fail, or pass again
+    pub fn set_healthy(&self, healthy: bool) {
+        self.state.healthy.store(healthy, Ordering::Relaxed);
+    }
+
+    /// Serve until the server fails
+    pub async fn wait(mut self) {
+        let _ = (&mut self.server).await;
+    }
+}
+
+impl Drop for MockEnclave {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
@@ -1,8 +1,23 @@
+pub mod beacon;
 pub mod parse;
 
 #[cfg(feature = "dev")]
 pub mod abi;
 #[cfg(feature = "dev")]
+pub mod contracts;
+#[cfg(feature = "dev")]
 pub mod ethereum;
 #[cfg(feature = "dev")]
+pub mod fallback_client;
+#[cfg(feature = "dev")]
+pub mod guardian_signatures;
+#[cfg(feature = "dev")]
+pub mod mock_enclave;
+#[cfg(feature = "dev")]
+pub mod multicall;
+#[cfg(feature = "dev")]
+pub mod transaction;
+#[cfg(feature = "dev")]
+pub mod transport;
+#[cfg(feature = "dev")]
 pub mod wallet;
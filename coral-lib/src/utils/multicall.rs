@@ -0,0 +1,582 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use ethers::abi::{Detokenize, Function, Token};
+use ethers::contract::multicall_contract::{Call3, Multicall3};
+use ethers::contract::{ContractCall, MULTICALL_ADDRESS};
+use ethers::prelude::{BlockId, BlockNumber, Bytes, Middleware};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use futures_util::future::try_join_all;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::utils::fallback_client::FallbackClient;
+
+/// Calls per `aggregate3`, to stay under the `eth_call` gas limit of most nodes
+const MULTICALL_CHUNK_SIZE: usize = 500;
+/// Requests per JSON-RPC batch, most providers reject larger batches
+const JSON_RPC_BATCH_SIZE: usize = 100;
+
+fn call_error(error_msg: String) -> ServerErrorResponse {
+    tracing::error!("{error_msg}");
+    ServerErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ServerErrorCode::EvmCallError,
+        error_msg,
+    )
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Multicall3,
+    JsonRpcBatch,
+    /// Without an HTTP endpoint to batch on, e.g. over WebSocket or IPC
+    Concurrent,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    id: usize,
+    result: Option<Bytes>,
+    error: Option<JsonRpcError>,
+}
+
+/// A contract call without its return type
+#[derive(Clone, Debug)]
+struct RawCall {
+    tx: TypedTransaction,
+    function: Function,
+    block: Option<BlockId>,
+}
+
+impl<M, D> From<ContractCall<M, D>> for RawCall {
+    fn from(call: ContractCall<M, D>) -> Self {
+        Self {
+            tx: call.tx,
+            function: call.function,
+            block: call.block,
+        }
+    }
+}
+
+impl RawCall {
+    fn decode(&self, data: &[u8]) -> AppServerResult<Vec<Token>> {
+        self.function.decode_output(data).map_err(|err| {
+            call_error(format!(
+                "Failed to decode {} result: {err}",
+                self.function.name
+            ))
+        })
+    }
+}
+
+fn detokenize<D: Detokenize>(function: &Function, tokens: Vec<Token>) -> AppServerResult<D> {
+    D::from_tokens(tokens)
+        .map_err(|err| call_error(format!("Failed to decode {} result: {err}", function.name)))
+}
+
+/// Indices of the calls pinned to each block, in order of first appearance
+fn group_by_block(calls: &[RawCall]) -> Vec<(Option<BlockId>, Vec<usize>)> {
+    let mut groups: Vec<(Option<BlockId>, Vec<usize>)> = Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        match groups.iter_mut().find(|(block, _)| *block == call.block) {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((call.block, vec![index])),
+        }
+    }
+    groups
+}
+
+/// Position of a call in a [`CallBatch`], to read its result from the [`BatchResults`]
+#[derive(Debug)]
+pub struct BatchSlot<D> {
+    index: usize,
+    datatype: PhantomData<fn() -> D>,
+}
+
+/// Calls of different return types, run together by [`BatchReader::run`]
+#[derive(Clone, Debug, Default)]
+pub struct CallBatch {
+    calls: Vec<RawCall>,
+}
+
+impl CallBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<M, D: Detokenize>(&mut self, call: ContractCall<M, D>) -> BatchSlot<D> {
+        self.calls.push(call.into());
+        BatchSlot {
+            index: self.calls.len() - 1,
+            datatype: PhantomData,
+        }
+    }
+}
+
+/// Decoded outputs of a [`CallBatch`]
+#[derive(Debug)]
+pub struct BatchResults {
+    calls: Vec<RawCall>,
+    outputs: Vec<Vec<Token>>,
+}
+
+impl BatchResults {
+    pub fn get<D: Detokenize>(&self, slot: BatchSlot<D>) -> AppServerResult<D> {
+        let function = &self.calls[slot.index].function;
+        detokenize(function, self.outputs[slot.index].clone())
+    }
+}
+
+/// Runs read-only contract calls in as few round trips as possible:
+/// through Multicall3 when it is deployed, else as JSON-RPC batch requests
+/// over the client's HTTP endpoints, else concurrently over the client's connection.
+/// Results are returned in the order of the calls, any failed call fails the batch.
+/// Calls pinned to different blocks run at their own block.
+#[derive(Clone, Debug)]
+pub struct BatchReader<M> {
+    client: Arc<M>,
+    backend: Backend,
+}
+
+impl<M: Middleware<Provider = FallbackClient> + 'static> BatchReader<M> {
+    pub async fn new(client: Arc<M>) -> AppServerResult<Self> {
+        let code = client
+            .get_code(MULTICALL_ADDRESS, None)
+            .await
+            .map_err(|err| {
+                let error_msg = "Failed to fetch Multicall3 code";
+                tracing::error!("{error_msg}: {err}");
+                ServerErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ServerErrorCode::EvmFetchCodeError,
+                    err.to_string(),
+                )
+            })?;
+        let backend = if !code.is_empty() {
+            Backend::Multicall3
+        } else if client.provider().as_ref().supports_batches() {
+            tracing::debug!("Multicall3 is not deployed, batching calls as JSON-RPC requests");
+            Backend::JsonRpcBatch
+        } else {
+            tracing::debug!("Multicall3 is not deployed, sending calls concurrently");
+            Backend::Concurrent
+        };
+        Ok(Self { client, backend })
+    }
+
+    pub async fn call<D: Detokenize>(
+        &self,
+        calls: Vec<ContractCall<M, D>>,
+    ) -> AppServerResult<Vec<D>> {
+        let calls: Vec<RawCall> = calls.into_iter().map(RawCall::from).collect();
+        let outputs = self.call_raw(&calls).await?;
+        calls
+            .iter()
+            .zip(outputs)
+            .map(|(call, tokens)| detokenize(&call.function, tokens))
+            .collect()
+    }
+
+    pub async fn run(&self, batch: CallBatch) -> AppServerResult<BatchResults> {
+        let outputs = self.call_raw(&batch.calls).await?;
+        Ok(BatchResults {
+            calls: batch.calls,
+            outputs,
+        })
+    }
+
+    async fn call_raw(&self, calls: &[RawCall]) -> AppServerResult<Vec<Vec<Token>>> {
+        let mut outputs = Vec::with_capacity(calls.len());
+        match self.backend {
+            Backend::Multicall3 => {
+                // `aggregate3` runs every call at one block, calls pinned to different
+                // blocks go in different multicalls
+                let mut block_outputs: Vec<Option<Vec<Token>>> = vec![None; calls.len()];
+                for (block, indices) in group_by_block(calls) {
+                    for chunk in indices.chunks(MULTICALL_CHUNK_SIZE) {
+                        let chunk_calls: Vec<&RawCall> =
+                            chunk.iter().map(|&index| &calls[index]).collect();
+                        let results = self.multicall(&chunk_calls, block).await?;
+                        for (&index, tokens) in chunk.iter().zip(results) {
+                            block_outputs[index] = Some(tokens);
+                        }
+                    }
+                }
+                outputs.extend(block_outputs.into_iter().flatten());
+            }
+            Backend::JsonRpcBatch => {
+                for chunk in calls.chunks(JSON_RPC_BATCH_SIZE) {
+                    outputs.extend(self.json_rpc_batch(chunk).await?);
+                }
+            }
+            Backend::Concurrent => {
+                let responses = try_join_all(calls.iter().map(|call| async move {
+                    let data = self
+                        .client
+                        .call(&call.tx, call.block)
+                        .await
+                        .map_err(|err| {
+                            call_error(format!("{} call failed: {err}", call.function.name))
+                        })?;
+                    call.decode(&data)
+                }))
+                .await?;
+                outputs.extend(responses);
+            }
+        }
+        Ok(outputs)
+    }
+
+    async fn multicall(
+        &self,
+        calls: &[&RawCall],
+        block: Option<BlockId>,
+    ) -> AppServerResult<Vec<Vec<Token>>> {
+        let call3s = calls
+            .iter()
+            .map(|call| {
+                let target = call.tx.to_addr().copied().ok_or_else(|| {
+                    call_error(format!("{} call has no target", call.function.name))
+                })?;
+                Ok(Call3 {
+                    target,
+                    allow_failure: false,
+                    call_data: call.tx.data().cloned().unwrap_or_default(),
+                })
+            })
+            .collect::<AppServerResult<Vec<Call3>>>()?;
+
+        let multicall = Multicall3::new(MULTICALL_ADDRESS, self.client.clone());
+        let mut aggregate = multicall.aggregate_3(call3s);
+        if let Some(block) = block {
+            aggregate = aggregate.block(block);
+        }
+        let results = aggregate
+            .call()
+            .await
+            .map_err(|err| call_error(format!("Multicall3 call failed: {err}")))?;
+
+        if results.len() != calls.len() {
+            return Err(call_error(format!(
+                "Multicall3 returned {} results for {} calls",
+                results.len(),
+                calls.len()
+            )));
+        }
+        calls
+            .iter()
+            .zip(results)
+            .map(|(call, result)| call.decode(&result.return_data))
+            .collect()
+    }
+
+    async fn json_rpc_batch(&self, calls: &[RawCall]) -> AppServerResult<Vec<Vec<Token>>> {
+        let requests: Vec<serde_json::Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                let block = call.block.unwrap_or(BlockId::from(BlockNumber::Latest));
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "eth_call",
+                    "params": [call.tx, block],
+                })
+            })
+            .collect();
+
+        let mut responses: Vec<JsonRpcResponse> = self
+            .client
+            .provider()
+            .as_ref()
+            .batch_request(&requests)
+            .await
+            .map_err(|err| call_error(format!("JSON-RPC batch request failed: {err}")))?;
+        // responses of a batch may come back in any order
+        responses.sort_by_key(|response| response.id);
+
+        if responses.len() != calls.len() {
+            return Err(call_error(format!(
+                "JSON-RPC batch returned {} responses for {} calls",
+                responses.len(),
+                calls.len()
+            )));
+        }
+
+        calls
+            .iter()
+            .zip(responses)
+            .map(|(call, response)| {
+                if let Some(err) = response.error {
+                    return Err(call_error(format!(
+                        "{} call failed: {}",
+                        call.function.name, err.message
+                    )));
+                }
+                call.decode(&response.result.unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use ethers::abi::{parse_abi, AbiDecode, AbiEncode};
+    use ethers::contract::multicall_contract::{
+        Aggregate3Call, Aggregate3Return, Result as Call3Result,
+    };
+    use ethers::contract::Contract;
+    use ethers::providers::Provider;
+    use ethers::types::{Address, U256};
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::utils::fallback_client::RpcConfig;
+
+    type Client = Provider<FallbackClient>;
+
+    /// Node answering `balanceOf(holder)` with `1000 * block + last byte of holder`
+    #[derive(Debug, Default)]
+    struct MockNode {
+        multicall_deployed: bool,
+        /// Answer batches in reverse order
+        reverse_batches: bool,
+        /// Leave out the last response of a batch
+        drop_response: bool,
+        /// `eth_call` requests, a multicall counts once
+        eth_calls: AtomicUsize,
+    }
+
+    fn block_number(block: &Value) -> u64 {
+        block
+            .as_str()
+            .and_then(|block| block.strip_prefix("0x"))
+            .and_then(|block| u64::from_str_radix(block, 16).ok())
+            .unwrap_or_default()
+    }
+
+    fn balance_of(data: &[u8], block: u64) -> Bytes {
+        // last byte of the address, after the selector
+        let holder = data[35];
+        U256::from(1000 * block + holder as u64).encode().into()
+    }
+
+    impl MockNode {
+        fn answer(&self, request: &Value) -> Value {
+            let result = match request["method"].as_str() {
+                Some("eth_getCode") if self.multicall_deployed => json!("0x01"),
+                Some("eth_getCode") => json!("0x"),
+                Some("eth_call") => {
+                    self.eth_calls.fetch_add(1, Ordering::Relaxed);
+                    let tx = &request["params"][0];
+                    let data = tx.get("data").or(tx.get("input")).cloned().unwrap();
+                    let data: Bytes = serde_json::from_value(data).unwrap();
+                    let to: Address = serde_json::from_value(tx["to"].clone()).unwrap();
+                    let block = block_number(&request["params"][1]);
+                    if to == MULTICALL_ADDRESS {
+                        let aggregate = Aggregate3Call::decode(&data).unwrap();
+                        let results = aggregate
+                            .calls
+                            .iter()
+                            .map(|call| Call3Result {
+                                success: true,
+                                return_data: balance_of(&call.call_data, block),
+                            })
+                            .collect();
+                        json!(Bytes::from(
+                            Aggregate3Return {
+                                return_data: results
+                            }
+                            .encode()
+                        ))
+                    } else {
+                        json!(balance_of(&data, block))
+                    }
+                }
+                method => panic!("Unexpected method {method:?}"),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        }
+
+        fn answer_batch(&self, requests: &[Value]) -> Vec<Value> {
+            let mut responses: Vec<Value> = requests
+                .iter()
+                .map(|request| self.answer(request))
+                .collect();
+            if self.reverse_batches {
+                responses.reverse();
+            }
+            if self.drop_response {
+                responses.pop();
+            }
+            responses
+        }
+    }
+
+    async fn serve_http(node: Arc<MockNode>) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(body): Json<Value>| async move {
+                match body.as_array() {
+                    Some(requests) => Json(Value::Array(node.answer_batch(requests))),
+                    None => Json(node.answer(&body)),
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        endpoint
+    }
+
+    /// IPC socket answering each JSON-RPC request of the stream
+    async fn serve_ipc(node: Arc<MockNode>) -> String {
+        let path = std::env::temp_dir().join(format!("coral-multicall-{}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+            let mut chunk = [0; 4096];
+            loop {
+                let read = stream.read(&mut chunk).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                buffer.extend_from_slice(&chunk[..read]);
+                let mut requests = serde_json::Deserializer::from_slice(&buffer).into_iter();
+                let mut responses = Vec::new();
+                while let Some(Ok(request)) = requests.next() {
+                    responses.push(node.answer(&request));
+                }
+                let parsed = requests.byte_offset();
+                buffer.drain(..parsed);
+                for response in responses {
+                    let response = serde_json::to_vec(&response).unwrap();
+                    stream.write_all(&response).await.unwrap();
+                }
+            }
+        });
+        path.display().to_string()
+    }
+
+    async fn reader(endpoint: String) -> (Arc<Client>, BatchReader<Client>) {
+        let client = FallbackClient::connect(&[endpoint], RpcConfig::default())
+            .await
+            .unwrap();
+        let client = Arc::new(Provider::new(client));
+        let reader = BatchReader::new(client.clone()).await.unwrap();
+        (client, reader)
+    }
+
+    fn balance_call(
+        client: &Arc<Client>,
+        holder: u8,
+        block: Option<u64>,
+    ) -> ContractCall<Client, U256> {
+        let abi = parse_abi(&["function balanceOf(address) view returns (uint256)"]).unwrap();
+        let contract = Contract::new(Address::repeat_byte(0x70), abi, client.clone());
+        let call = contract
+            .method::<_, U256>("balanceOf", Address::repeat_byte(holder))
+            .unwrap();
+        match block {
+            Some(block) => call.block(block),
+            None => call,
+        }
+    }
+
+    fn balances(results: Vec<U256>) -> Vec<u64> {
+        results.iter().map(U256::as_u64).collect()
+    }
+
+    #[tokio::test]
+    async fn multicall3_runs_calls_at_their_block() {
+        let node = Arc::new(MockNode {
+            multicall_deployed: true,
+            ..Default::default()
+        });
+        let (client, reader) = reader(serve_http(node.clone()).await).await;
+        assert!(matches!(reader.backend, Backend::Multicall3));
+
+        let calls = vec![
+            balance_call(&client, 1, None),
+            balance_call(&client, 2, Some(5)),
+            balance_call(&client, 3, None),
+            balance_call(&client, 4, Some(5)),
+        ];
+        let results = reader.call(calls).await.unwrap();
+        assert_eq!(balances(results), vec![1, 5002, 3, 5004]);
+        // one multicall per block
+        assert_eq!(node.eth_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_batch_orders_responses_by_id() {
+        let node = Arc::new(MockNode {
+            reverse_batches: true,
+            ..Default::default()
+        });
+        let (client, reader) = reader(serve_http(node.clone()).await).await;
+        assert!(matches!(reader.backend, Backend::JsonRpcBatch));
+
+        let mut batch = CallBatch::new();
+        let first = batch.add(balance_call(&client, 1, None));
+        let second = batch.add(balance_call(&client, 2, Some(7)));
+        let third = batch.add(balance_call(&client, 3, None));
+        let results = reader.run(batch).await.unwrap();
+        assert_eq!(results.get(first).unwrap().as_u64(), 1);
+        assert_eq!(results.get(second).unwrap().as_u64(), 7002);
+        assert_eq!(results.get(third).unwrap().as_u64(), 3);
+        assert_eq!(node.eth_calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_batch_rejects_missing_responses() {
+        let node = Arc::new(MockNode {
+            drop_response: true,
+            ..Default::default()
+        });
+        let (client, reader) = reader(serve_http(node).await).await;
+
+        let calls = (1..=3)
+            .map(|holder| balance_call(&client, holder, None))
+            .collect();
+        let err = reader.call(calls).await.unwrap_err();
+        assert_eq!(
+            err.response.result.error_code,
+            ServerErrorCode::EvmCallError
+        );
+        assert_eq!(
+            err.to_string(),
+            "JSON-RPC batch returned 2 responses for 3 calls"
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_without_http_endpoint() {
+        let node = Arc::new(MockNode::default());
+        let (client, reader) = reader(serve_ipc(node.clone()).await).await;
+        assert!(matches!(reader.backend, Backend::Concurrent));
+
+        let calls = vec![
+            balance_call(&client, 1, None),
+            balance_call(&client, 2, Some(3)),
+        ];
+        let results = reader.call(calls).await.unwrap();
+        assert_eq!(balances(results), vec![1, 3002]);
+        assert_eq!(node.eth_calls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,189 @@
+use axum::http::StatusCode;
+use ethers::abi::Abi;
+use ethers::prelude::{
+    types::{transaction::eip2718::TypedTransaction, Transaction, TransactionReceipt, H256, U256},
+    JsonRpcClient, Middleware, MiddlewareError, PendingTransaction, Provider,
+};
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+use crate::utils::abi::revert::decode_revert_data;
+
+/// Overrides applied to every transaction coral sends.
+/// Anything left as `None` falls back to the node's estimates
+#[derive(Clone, Debug)]
+pub struct TransactionOptions {
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub gas_limit: Option<U256>,
+    pub gas_multiplier: Option<f64>,
+    pub nonce: Option<U256>,
+    pub confirmations: usize,
+}
+
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        Self {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            gas_limit: None,
+            gas_multiplier: None,
+            nonce: None,
+            confirmations: 1,
+        }
+    }
+}
+
+impl TransactionOptions {
+    /// Set nonce and fee caps on the transaction
+    pub fn apply(&self, tx: &mut TypedTransaction) {
+        if let Some(nonce) = self.nonce {
+            tx.set_nonce(nonce);
+        }
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                if let Some(max_fee_per_gas) = self.max_fee_per_gas {
+                    inner.max_fee_per_gas = Some(max_fee_per_gas);
+                }
+                if let Some(max_priority_fee_per_gas) = self.max_priority_fee_per_gas {
+                    inner.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+                }
+            }
+            _ => {
+                if let Some(max_fee_per_gas) = self.max_fee_per_gas {
+                    tx.set_gas_price(max_fee_per_gas);
+                }
+            }
+        }
+    }
+
+    /// Gas limit to use given the node's estimate
+    pub fn gas_limit(&self, estimated_gas: U256) -> U256 {
+        if let Some(gas_limit) = self.gas_limit {
+            return gas_limit;
+        }
+        match self.gas_multiplier {
+            Some(multiplier) => scale(estimated_gas, multiplier),
+            None => estimated_gas,
+        }
+    }
+}
+
+/// Multiply `value` by `multiplier` with a precision of 3 decimals
+pub fn scale(value: U256, multiplier: f64) -> U256 {
+    let multiplier = (multiplier * 1000.0).round() as u64;
+    value.saturating_mul(U256::from(multiplier)) / U256::from(1000)
+}
+
+fn simulation_error<E: MiddlewareError>(
+    err: E,
+    error_code: ServerErrorCode,
+    abis: &[(&str, &Abi)],
+) -> ServerErrorResponse {
+    let revert_data = err
+        .as_error_response()
+        .and_then(|rpc_err| rpc_err.as_revert_data());
+
+    match revert_data {
+        Some(data) => {
+            let error_msg = match decode_revert_data(&data, abis) {
+                Some(decoded) => format!("Transaction would revert: {decoded}"),
+                None if data.is_empty() => "Transaction would revert without reason".to_string(),
+                None => format!("Transaction would revert with unknown error: {data}"),
+            };
+            tracing::error!("{error_msg}");
+            ServerErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                ServerErrorCode::EvmSmartContractRevert,
+                error_msg,
+            )
+        }
+        None => {
+            let error_msg = "Failed to simulate transaction";
+            tracing::error!("{error_msg}: {err}");
+            ServerErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_code,
+                format!("{error_msg}: {err}"),
+            )
+        }
+    }
+}
+
+/// Run `eth_call` and `eth_estimateGas` on the transaction.
+/// Reverts are decoded against the custom errors of `abis`.
+/// Returns the estimated gas
+pub async fn simulate_transaction<M: Middleware>(
+    client: &M,
+    tx: &TypedTransaction,
+    abis: &[(&str, &Abi)],
+) -> AppServerResult<U256> {
+    client
+        .call(tx, None)
+        .await
+        .map_err(|err| simulation_error(err, ServerErrorCode::EvmCallError, abis))?;
+
+    let gas = client
+        .estimate_gas(tx, None)
+        .await
+        .map_err(|err| simulation_error(err, ServerErrorCode::EvmEstimateGasError, abis))?;
+    Ok(gas)
+}
+
+/// Apply `options`, simulate the transaction and send it.
+/// Returns the transaction hash without waiting for it to be mined
+pub async fn send_transaction<M: Middleware>(
+    client: &M,
+    mut tx: TypedTransaction,
+    abis: &[(&str, &Abi)],
+    options: &TransactionOptions,
+) -> AppServerResult<H256> {
+    options.apply(&mut tx);
+
+    let estimated_gas = simulate_transaction(client, &tx, abis).await?;
+    if tx.gas().is_none() {
+        tx.set_gas(options.gas_limit(estimated_gas));
+    }
+
+    let pending_tx = client.send_transaction(tx, None).await.map_err(|err| {
+        let error_msg = "Failed to send transaction";
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ServerErrorCode::EvmSendTransactionError,
+            format!("{error_msg}: {err}"),
+        )
+    })?;
+    Ok(pending_tx.tx_hash())
+}
+
+/// Wait until the transaction has the given number of confirmations
+pub async fn wait_for_transaction<M: Middleware>(
+    client: &M,
+    tx_hash: H256,
+    confirmations: usize,
+) -> AppServerResult<TransactionReceipt> {
+    let pending_tx =
+        PendingTransaction::new(tx_hash, client.provider()).confirmations(confirmations);
+    super::ethereum::get_transaction_receipt(pending_tx.await)
+}
+
+/// Fetch a transaction that has not been mined yet
+pub async fn get_pending_transaction<J, E>(
+    provider: &Provider<J>,
+    tx_hash: H256,
+) -> AppServerResult<Transaction>
+where
+    J: JsonRpcClient<Error = E>,
+{
+    let tx = super::ethereum::get_transaction(provider, tx_hash).await?;
+    if tx.block_number.is_some() {
+        let error_msg = "Transaction has already been mined";
+        tracing::error!("{error_msg}");
+        return Err(ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::EvmTransactionAlreadyMined,
+            error_msg.to_string(),
+        ));
+    }
+    Ok(tx)
+}
@@ -0,0 +1,204 @@
+use std::fmt::{self, Debug};
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use ethers::providers::{
+    Http, HttpClientError, HttpRateLimitRetryPolicy, Ipc, IpcError, JsonRpcClient, JsonRpcError,
+    ProviderError, PubsubClient, RetryPolicy, RpcError, Ws, WsClientError,
+};
+use ethers::types::U256;
+use futures_util::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::value::RawValue;
+use url::Url;
+
+use crate::error::{AppServerResult, ServerErrorCode, ServerErrorResponse};
+
+/// Reconnection attempts after a WebSocket connection drops
+const WS_RECONNECTS: usize = 5;
+
+pub type NotificationStream = Pin<Box<dyn Stream<Item = Box<RawValue>> + Send>>;
+
+/// A connection to one RPC endpoint
+#[derive(Clone, Debug)]
+pub enum Transport {
+    Http(Http),
+    Ws(Ws),
+    Ipc(Ipc),
+}
+
+#[derive(Debug)]
+pub enum TransportError {
+    Http(HttpClientError),
+    Ws(WsClientError),
+    Ipc(IpcError),
+    /// Subscriptions over HTTP
+    PubsubNotSupported,
+    /// JSON-RPC batch requests, sent over HTTP only
+    Batch(reqwest::Error),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "{err}"),
+            Self::Ws(err) => write!(f, "{err}"),
+            Self::Ipc(err) => write!(f, "{err}"),
+            Self::PubsubNotSupported => write!(f, "Subscriptions are not supported over HTTP"),
+            Self::Batch(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+impl RpcError for TransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Http(err) => err.as_error_response(),
+            Self::Ws(err) => err.as_error_response(),
+            Self::Ipc(err) => err.as_error_response(),
+            Self::PubsubNotSupported | Self::Batch(_) => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Http(err) => err.as_serde_error(),
+            Self::Ws(err) => err.as_serde_error(),
+            Self::Ipc(err) => err.as_serde_error(),
+            Self::PubsubNotSupported | Self::Batch(_) => None,
+        }
+    }
+}
+
+fn connection_error(endpoint: &str, err: String) -> ServerErrorResponse {
+    let error_msg = format!("Failed to connect to RPC endpoint '{endpoint}'");
+    tracing::error!("{error_msg}: {err}");
+    ServerErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ServerErrorCode::EvmConnectionError,
+        format!("{error_msg}: {err}"),
+    )
+}
+
+impl Transport {
+    /// `http://` and `https://` URLs, `ws://` and `wss://` URLs, or else an IPC socket path
+    pub async fn connect(endpoint: &str) -> AppServerResult<Self> {
+        let scheme = endpoint.split_once("://").map(|(scheme, _)| scheme);
+        match scheme {
+            Some("http") | Some("https") => {
+                let url = Url::parse(endpoint).map_err(|err| {
+                    let error_msg = "Invalid RPC URL";
+                    tracing::error!("{error_msg}: {err}");
+                    ServerErrorResponse::new(
+                        StatusCode::BAD_REQUEST,
+                        ServerErrorCode::HttpUrlError,
+                        err.to_string(),
+                    )
+                })?;
+                Ok(Self::Http(Http::new(url)))
+            }
+            Some("ws") | Some("wss") => {
+                let ws = Ws::connect_with_reconnects(endpoint, WS_RECONNECTS)
+                    .await
+                    .map_err(|err| connection_error(endpoint, err.to_string()))?;
+                Ok(Self::Ws(ws))
+            }
+            Some(scheme) => Err(connection_error(
+                endpoint,
+                format!("unsupported scheme '{scheme}'"),
+            )),
+            None => {
+                let ipc = Ipc::connect(endpoint)
+                    .await
+                    .map_err(|err| connection_error(endpoint, err.to_string()))?;
+                Ok(Self::Ipc(ipc))
+            }
+        }
+    }
+
+    pub fn supports_subscriptions(&self) -> bool {
+        !matches!(self, Self::Http(_))
+    }
+
+    /// URL to send JSON-RPC batch requests to, HTTP endpoints only
+    pub fn batch_url(&self) -> Option<&Url> {
+        match self {
+            Self::Http(http) => Some(http.url()),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(err: TransportError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+impl TransportError {
+    /// Whether another endpoint could succeed where this one failed: connection errors,
+    /// unreadable responses and rate limits, but not JSON-RPC errors such as reverts
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Http(err @ HttpClientError::JsonRpcError(_)) => {
+                HttpRateLimitRetryPolicy.should_retry(err)
+            }
+            Self::Http(_) | Self::Batch(_) => true,
+            Self::PubsubNotSupported => false,
+            err => err.as_error_response().is_none(),
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for Transport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Self::Http(http) => http
+                .request(method, params)
+                .await
+                .map_err(TransportError::Http),
+            Self::Ws(ws) => ws.request(method, params).await.map_err(TransportError::Ws),
+            Self::Ipc(ipc) => ipc
+                .request(method, params)
+                .await
+                .map_err(TransportError::Ipc),
+        }
+    }
+}
+
+impl PubsubClient for Transport {
+    type NotificationStream = NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        match self {
+            Self::Http(_) => Err(TransportError::PubsubNotSupported),
+            Self::Ws(ws) => ws
+                .subscribe(id)
+                .map(|stream| stream.boxed())
+                .map_err(TransportError::Ws),
+            Self::Ipc(ipc) => ipc
+                .subscribe(id)
+                .map(|stream| stream.boxed())
+                .map_err(TransportError::Ipc),
+        }
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(_) => Err(TransportError::PubsubNotSupported),
+            Self::Ws(ws) => ws.unsubscribe(id).map_err(TransportError::Ws),
+            Self::Ipc(ipc) => ipc.unsubscribe(id).map_err(TransportError::Ipc),
+        }
+    }
+}
@@ -1,7 +1,36 @@
+use axum::http::StatusCode;
 use ethers::prelude::{rand, LocalWallet};
 
+use crate::{
+    error::{AppServerResult, ServerErrorCode, ServerErrorResponse},
+    strip_0x_prefix,
+};
+
 /// Generate a random wallet.
 /// Mostly used for calling view and pure functions
 pub fn generate_random_wallet() -> LocalWallet {
     LocalWallet::new(&mut rand::thread_rng())
 }
+
+/// Parse a hex encoded private key into a wallet
+pub fn wallet_from_private_key(private_key: &str) -> AppServerResult<LocalWallet> {
+    let priv_key_bytes = hex::decode(strip_0x_prefix(private_key)).map_err(|err| {
+        let error_msg = "Failed to decode private key";
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ParseError,
+            error_msg.to_string(),
+        )
+    })?;
+    let wallet = LocalWallet::from_bytes(&priv_key_bytes).map_err(|err| {
+        let error_msg = "Invalid private key";
+        tracing::error!("{error_msg}: {err}");
+        ServerErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            ServerErrorCode::ParseError,
+            error_msg.to_string(),
+        )
+    })?;
+    Ok(wallet)
+}